@@ -0,0 +1,265 @@
+// Command line access to the same parsing / querying the app uses - ex. `mdt query "options where pros > cons" decisions/`
+
+use mdt_core::ahp::{prioritize, PriorityVector};
+use mdt_core::action_items::{open_action_items, open_workspace_action_items};
+use mdt_core::analysis::analyze;
+use mdt_core::config::Config;
+use mdt_core::diff::diff;
+use mdt_core::file_parse::{parse_file, parse_str_with};
+use mdt_core::file_write::{match_line_endings, write_file_atomic, SaveOptions};
+use mdt_core::merge::{merge, merge_to_string, ConflictKind};
+use mdt_core::query::Query;
+use mdt_core::sensitivity::{sensitivity, to_csv};
+use mdt_core::structs::{Nodes, NodeType};
+use mdt_core::templates::{find_template, new_from_template, templates, user_template_dir, Template};
+use mdt_core::tracking::{track_nodes, track_workspace, TrackedNode};
+use mdt_core::workspace::Workspace;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitCode};
+
+const USAGE: &str = "Usage: mdt <command> [args]
+
+Commands:
+  query <query> <file or directory>   Print the nodes matching a query
+  new <template> <file> [name=value ...]
+                                      Create a decision file from a template, filling in its placeholders
+  templates                           Print the built in & user templates with their placeholders
+  analyze <file>                      Print each Option's expected value & the optimal path through Chance nodes
+  sensitivity <file>                  Print CSV of where each weight / probability flips the recommended Option
+  ahp <file>                          Print priorities & consistency of #ahp pairwise comparisons & the Option ranking
+  diff <old file> <new file>          Print the node changes between two versions of a file
+  report <file or directory>          Print assumptions that aren't validated & high risks
+  tasks <file or directory>           Print open [ ] action items with their owners & due dates
+  merge-driver <base> <ours> <theirs> [path]
+                                      Git merge driver - merges theirs into ours, fails if conflicts remain.
+                                      path is the file's path in the repo, for finding its .mdt.toml";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let res = match args.first().map(String::as_str) {
+        Some("query") => run_query(&args[1..]),
+        Some("new") => run_new(&args[1..]),
+        Some("templates") => run_templates(&args[1..]),
+        Some("analyze") => run_analyze(&args[1..]),
+        Some("sensitivity") => run_sensitivity(&args[1..]),
+        Some("ahp") => run_ahp(&args[1..]),
+        Some("diff") => run_diff(&args[1..]),
+        Some("report") => run_report(&args[1..]),
+        Some("tasks") => run_tasks(&args[1..]),
+        Some("merge-driver") => run_merge_driver(&args[1..]),
+        _ => {
+            eprintln!("{}", USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Err(err) = res {
+        eprintln!("mdt: {}", err);
+        return ExitCode::FAILURE;
+    }
+    return ExitCode::SUCCESS;
+}
+
+/// Prints `<file>:<file_order>: <text>` per match - a directory is opened as a workspace
+fn run_query(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let [query, path] = args else { Err(USAGE)? };
+    let query = Query::parse(query)?;
+    let path = PathBuf::from(path);
+    if path.is_dir() {
+        for file in Workspace::open(path)?.files {
+            match &file.nodes {
+                Ok(nodes) => print_matches(&file.path, nodes, &query),
+                Err(err) => eprintln!("mdt: skipping {}: {}", file.path.display(), err),
+            }
+        }
+    } else {
+        print_matches(&path, &parse_file(path.clone())?, &query);
+    }
+    return Ok(());
+}
+
+fn print_matches(path: &Path, nodes: &Nodes, query: &Query) {
+    for file_order in query.run(nodes) {
+        println!("{}:{}: {}", path.display(), file_order, nodes.nodes[file_order as usize].text);
+    }
+}
+
+/// Template is a name from `mdt templates` or a path to a template file
+fn run_new(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let [template, path, values @ ..] = args else { Err(USAGE)? };
+    let values: BTreeMap<String, String> = values.iter()
+        .map(|value| value.split_once('=').map(|(name, value)| (name.to_string(), value.to_string())))
+        .collect::<Option<_>>()
+        .ok_or("Placeholder values are written name=value")?;
+    let template_path = Path::new(template);
+    let template = if template_path.extension().is_some_and(|extension| extension == "md") && template_path.is_file() {
+        Template::load(template_path)?
+    } else {
+        find_template(template, user_template_dir().as_deref())?
+    };
+    new_from_template(&template, &values, Path::new(path))?;
+    return Ok(());
+}
+
+/// Prints `<name>: <placeholders>` per template - user templates with their path
+fn run_templates(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let [] = args else { Err(USAGE)? };
+    for template in templates(user_template_dir().as_deref())? {
+        let path = template.path.map(|path| format!(" ({})", path)).unwrap_or_default();
+        println!("{}{}: {}", template.name, path, template.placeholders.join(", "));
+    }
+    return Ok(());
+}
+
+/// Prints each analyzed Decision's expected value, then `<file_order>: <text> = <expected value>` per Option - the
+/// optimal path's Options are marked with a `*`
+fn run_analyze(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let [path] = args else { Err(USAGE)? };
+    let nodes = parse_file(PathBuf::from(path))?;
+    let analyses = analyze(&nodes)?;
+    if analyses.is_empty() { Err(format!("{} has no Chance nodes or values to analyze", path))? }
+    for analysis in analyses {
+        println!("{}: {} = {}", analysis.decision, nodes.nodes[analysis.decision as usize].text, analysis.expected_value);
+        for value in analysis.values.iter().filter(|value| nodes.nodes[value.file_order as usize].type_is == Some(NodeType::Option)) {
+            let marker = if analysis.optimal_path.contains(&value.file_order) { "*" } else { " " };
+            println!("{} {}: {} = {}", marker, value.file_order, nodes.nodes[value.file_order as usize].text, value.expected_value);
+        }
+    }
+    return Ok(());
+}
+
+/// Prints tornado chart data as CSV - a row per weight / probability, widest swing first within each Decision
+fn run_sensitivity(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let [path] = args else { Err(USAGE)? };
+    let nodes = parse_file(PathBuf::from(path))?;
+    let reports = sensitivity(&nodes)?;
+    if reports.is_empty() { Err(format!("{} has no Decisions with 2 or more weighed Options", path))? }
+    print!("{}", to_csv(&nodes, &reports));
+    return Ok(());
+}
+
+/// Prints each compared Decision, its priority vectors with their consistency ratios, then its Options best first
+fn run_ahp(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let [path] = args else { Err(USAGE)? };
+    let nodes = parse_file(PathBuf::from(path))?;
+    let found = prioritize(&nodes);
+    if found.is_empty() { Err(format!("{} has no #ahp comparisons", path))? }
+    let text_of = |file_order: u32| &nodes.nodes[file_order as usize].text;
+    let print_vector = |label: &str, vector: &PriorityVector| {
+        println!("  {} (consistency ratio {:.3}):", label, vector.consistency_ratio);
+        for found in &vector.priorities {
+            println!("    {}: {} = {:.3}", found.file_order, text_of(found.file_order), found.priority);
+        }
+    };
+    for decision in found {
+        println!("{}: {}", decision.decision, text_of(decision.decision));
+        if let Some(criteria) = &decision.criteria { print_vector("Pros / Cons", criteria); }
+        if let Some(options) = &decision.options { print_vector("Options", options); }
+        println!("  Ranking:");
+        for ranked in &decision.ranking {
+            println!("    {}: {} = {:.3}", ranked.option, text_of(ranked.option), ranked.score);
+        }
+    }
+    return Ok(());
+}
+
+/// Prints one line per change - `+` added, `-` removed, `~` changed
+fn run_diff(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let [old_path, new_path] = args else { Err(USAGE)? };
+    let old = parse_file(PathBuf::from(old_path))?;
+    let new = parse_file(PathBuf::from(new_path))?;
+    for change in diff(&old, &new) {
+        println!("{}", change.describe(&old, &new));
+    }
+    return Ok(());
+}
+
+/// Prints `<file>:<file_order>: '<text>' on '<parent>' - <status> assumption`, then high risks with their rating
+fn run_report(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let [path] = args else { Err(USAGE)? };
+    let path = PathBuf::from(path);
+    let report = if path.is_dir() {
+        let workspace = Workspace::open(path)?;
+        for file in &workspace.files {
+            if let Err(err) = &file.nodes { eprintln!("mdt: skipping {}: {}", file.path.display(), err); }
+        }
+        track_workspace(&workspace)
+    } else {
+        track_nodes(&path.to_string_lossy(), &parse_file(path.clone())?)
+    };
+    let describe = |node: &TrackedNode| -> String {
+        let on = node.attached_to.as_ref().map_or(String::new(), |parent| format!(" on '{}'", parent));
+        return format!("{}:{}: '{}'{}", node.file_path, node.file_order, node.text, on);
+    };
+    for assumption in &report.assumptions {
+        println!("{} - {:?} assumption", describe(&assumption.node), assumption.status);
+    }
+    for risk in &report.risks {
+        println!("{} - risk, {:?} likelihood / {:?} impact", describe(&risk.node), risk.rating.likelihood, risk.rating.impact);
+    }
+    return Ok(());
+}
+
+/// Prints `<file>:<file_order>: <text> - @<owner>, due <date>` per open item - soonest due first within each file
+fn run_tasks(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let [path] = args else { Err(USAGE)? };
+    let path = PathBuf::from(path);
+    let items = if path.is_dir() {
+        let workspace = Workspace::open(path)?;
+        for file in &workspace.files {
+            if let Err(err) = &file.nodes { eprintln!("mdt: skipping {}: {}", file.path.display(), err); }
+        }
+        open_workspace_action_items(&workspace)
+    } else {
+        open_action_items(&path.to_string_lossy(), &parse_file(path.clone())?)
+    };
+    for item in items {
+        let mut details: Vec<String> = item.owners.iter().map(|owner| format!("@{}", owner)).collect();
+        details.extend(item.due.map(|due| format!("due {}", due)));
+        let details = if details.is_empty() { String::new() } else { format!(" - {}", details.join(", ")) };
+        println!("{}:{}: {}{}", item.file_path, item.file_order, item.text, details);
+    }
+    return Ok(());
+}
+
+/// Called by git as `mdt merge-driver %O %A %B %P` - the result is written over ours (%A)
+/// - files that aren't decision files fall back to git's own line based merge
+fn run_merge_driver(args: &[String]) -> Result<(), Box<dyn Error>> {
+    let (base_path, ours_path, theirs_path, repo_path) = match args {
+        [base_path, ours_path, theirs_path] => (base_path, ours_path, theirs_path, ours_path),
+        [base_path, ours_path, theirs_path, repo_path] => (base_path, ours_path, theirs_path, repo_path),
+        _ => Err(USAGE)?,
+    };
+    // The versions git hands over are temp files - the real path is where the file's .mdt.toml is found from
+    let config = Config::discover(Path::new(repo_path))?;
+    let parse_path = |path: &str| -> Result<Nodes, Box<dyn Error>> { return parse_str_with(&read_to_string(path)?, &config); };
+    let base_content = read_to_string(base_path)?;
+    let parsed = (
+        if base_content.is_empty() { Ok(Nodes::default()) } else { parse_str_with(&base_content, &config) }, //< Empty when both sides added the file
+        parse_path(ours_path),
+        parse_path(theirs_path),
+    );
+    let (Ok(base), Ok(ours), Ok(theirs)) = parsed else {
+        let status = Command::new("git").args(["merge-file", "-L", "ours", "-L", "base", "-L", "theirs", ours_path, base_path, theirs_path]).status()?;
+        return if status.success() { Ok(()) } else { Err(format!("conflicts in {}", ours_path).into()) };
+    };
+
+    let result = merge(&base, &ours, &theirs);
+    let ours_path = PathBuf::from(ours_path);
+    write_file_atomic(&ours_path, &match_line_endings(merge_to_string(&result, &config)?, &ours_path)?, &SaveOptions::default())?;
+    if result.conflicts.is_empty() {
+        return Ok(());
+    }
+    for conflict in &result.conflicts {
+        let text = &result.nodes.nodes[conflict.file_order as usize].text;
+        let reason = match conflict.kind {
+            ConflictKind::Edited => "edited on both sides",
+            ConflictKind::DeletedAndEdited => "deleted on one side but changed on the other - kept",
+            ConflictKind::Moved => "moved to different places on each side - kept ours",
+        };
+        eprintln!("mdt: {}:{}: '{}' {}", ours_path.display(), conflict.file_order, text, reason);
+    }
+    return Err(format!("{} conflicts in {}", result.conflicts.len(), ours_path.display()).into());
+}
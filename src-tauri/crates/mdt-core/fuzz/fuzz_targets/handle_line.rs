@@ -0,0 +1,12 @@
+#![no_main]
+// Lines the parser doesn't understand must be an error, never a panic
+
+use libfuzzer_sys::fuzz_target;
+use mdt_core::parsers::bullet_file_parser::BulletFileParser;
+
+fuzz_target!(|content: &str| {
+    let mut parser = BulletFileParser::new();
+    for line in content.lines() {
+        let _ = parser.handle_line(line);
+    }
+});
@@ -0,0 +1,13 @@
+#![no_main]
+// Whole files - parse_file is read_to_string + parse_str. Anything that parses must also survive a save unchanged
+
+use libfuzzer_sys::fuzz_target;
+use mdt_core::file_parse::parse_str;
+use mdt_core::file_write::nodes_to_string;
+use std::path::Path;
+
+fuzz_target!(|content: &str| {
+    let Ok(nodes) = parse_str(content) else { return; };
+    let written = nodes_to_string(&nodes, Path::new("fuzz.md")).unwrap();
+    assert_eq!(parse_str(&written).unwrap(), nodes, "content:\n{}\nwritten:\n{}", content, written);
+});
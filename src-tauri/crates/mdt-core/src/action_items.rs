@@ -0,0 +1,74 @@
+use crate::node_meta::{due_date, owners, without_checkbox, TaskState};
+use crate::structs::Nodes;
+use crate::workspace::Workspace;
+use serde::{Serialize, Deserialize};
+
+/// A node with a `[ ]` / `[x]` checkbox - ex. `N: [ ] Need to have meeting about this @zach #due-2026-11-01`
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct ActionItem {
+  pub file_path: String,
+  pub file_order: u32,
+  pub text: String, //< Without the checkbox
+  pub state: TaskState,
+  pub owners: Vec<String>,
+  pub due: Option<String>, //< `YYYY-MM-DD`
+  pub decision: Option<String>, //< Text of the Decision it falls under
+}
+
+/// Every checkbox in a file, in file order
+pub fn action_items(file_path: &str, nodes: &Nodes) -> Vec<ActionItem> {
+  return nodes.nodes.iter()
+    .filter_map(|node| Some(ActionItem{
+      file_path: file_path.to_string(),
+      file_order: node.file_order,
+      text: without_checkbox(&node.text).to_string(),
+      state: TaskState::from_text(&node.text)?,
+      owners: owners(&node.text),
+      due: due_date(&node.text),
+      decision: nodes.enclosing_decision(node.file_order).map(|decision| decision.text.clone()),
+    }))
+    .collect();
+}
+
+/// Items still to do - soonest due first, then ones without a due date in file order
+pub fn open_action_items(file_path: &str, nodes: &Nodes) -> Vec<ActionItem> {
+  let mut items: Vec<ActionItem> = action_items(file_path, nodes).into_iter().filter(|item| item.state == TaskState::Open).collect();
+  items.sort_by(|a, b| a.due.is_none().cmp(&b.due.is_none()).then_with(|| a.due.cmp(&b.due)).then(a.file_order.cmp(&b.file_order)));
+  return items;
+}
+
+/// Open items of every file that parsed - grouped by file, in the workspace's file order
+pub fn open_workspace_action_items(workspace: &Workspace) -> Vec<ActionItem> {
+  return workspace.files.iter()
+    .filter_map(|file| Some((file.path.to_string_lossy().to_string(), file.nodes.as_ref().ok()?)))
+    .flat_map(|(file_path, nodes)| open_action_items(&file_path, nodes))
+    .collect();
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::file_parse::parse_str;
+  use crate::file_write::nodes_to_string;
+  use std::path::Path;
+
+  const CONTENT: &str = "# Tasks (md-decision-trees)\n\nD: What about the other thing?\n* O: This way\n  \
+    * N: [ ] Benchmark it @ann\n* O: That way\n* N: [x] Ask the team\n* N: [ ] Need to have meeting about this @zach #due-2026-11-01\n\
+    * N: [X]Not a checkbox - no space";
+
+  #[test]
+  fn test_action_items() {
+    let nodes = parse_str(CONTENT).unwrap();
+    let items = action_items("tasks.md", &nodes);
+    let found: Vec<(u32, TaskState)> = items.iter().map(|item| (item.file_order, item.state)).collect();
+    assert_eq!(found, vec![(2, TaskState::Open), (4, TaskState::Done), (5, TaskState::Open)]);
+    assert_eq!(items[2].text, "Need to have meeting about this @zach #due-2026-11-01");
+    assert_eq!((items[2].owners.clone(), items[2].due.as_deref()), (vec!["zach".to_string()], Some("2026-11-01")));
+    assert_eq!(items[0].decision.as_deref(), Some("What about the other thing?"));
+
+    let open: Vec<u32> = open_action_items("tasks.md", &nodes).iter().map(|item| item.file_order).collect();
+    assert_eq!(open, vec![5, 2]); //< Due dates first
+    assert_eq!(nodes_to_string(&nodes, Path::new("tasks.md")).unwrap(), CONTENT); //< Checkboxes are written back as is
+  }
+}
@@ -0,0 +1,346 @@
+use crate::graph::Graph;
+use crate::history::Op;
+use crate::node_meta::{comparison, tags};
+use crate::scoring::ScoreModel;
+use crate::structs::{Node, Nodes, NodeType};
+use crate::validate::Diagnostic;
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+
+/// Tag of the Note under a Decision that holds its comparisons - ex. `N: Pairwise comparisons #ahp`
+pub const AHP_TAG: &str = "ahp";
+/// Comparisons are consistent enough to use up to this consistency ratio
+pub const CONSISTENCY_LIMIT: f64 = 0.1;
+/// Saaty's random consistency index by number of items compared - larger sets use the last one
+const RANDOM_INDEX: [f64; 11] = [0.0, 0.0, 0.0, 0.58, 0.9, 1.12, 1.24, 1.32, 1.41, 1.45, 1.49];
+
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub enum ComparedKind {
+  Criteria, //< The Decision's Pros / Cons - their priorities become weights, see scoring.rs
+  Options,
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct Priority {
+  pub file_order: u32,
+  pub priority: f64,
+}
+
+/// Principal eigenvector of a set of pairwise comparisons - priorities add up to 1
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct PriorityVector {
+  pub kind: ComparedKind,
+  pub priorities: Vec<Priority>, //< In file order
+  pub consistency_ratio: f64, //< Over CONSISTENCY_LIMIT means the comparisons contradict each other
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct RankedOption {
+  pub option: u32,
+  pub score: f64,
+}
+
+/// A Decision's comparisons worked out - Options are ranked by their own priorities if they're compared, else by
+/// their scores with the criteria priorities as weights
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct DecisionPriorities {
+  pub decision: u32,
+  pub criteria: Option<PriorityVector>,
+  pub options: Option<PriorityVector>,
+  pub ranking: Vec<RankedOption>, //< Best first
+}
+
+/// `a` matters `ratio` times more than `b`
+struct Comparison { a: u32, b: u32, ratio: f64 }
+
+/// Comparisons in a Decision's `#ahp` blocks, with the problems found reading them
+struct Comparisons<'a> {
+  graph: &'a Graph<'a>,
+  by_decision: BTreeMap<u32, Vec<(ComparedKind, Comparison)>>,
+  diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Comparisons<'a> {
+  fn read(graph: &'a Graph<'a>) -> Self {
+    let mut comparisons = Comparisons{graph, by_decision: BTreeMap::new(), diagnostics: Vec::new()};
+    for block in graph.nodes.nodes.iter().filter(|node| is_block(node)) {
+      let parent = block.parent_idxs.first().and_then(|idx| graph.nodes.nodes.get(*idx as usize));
+      let Some(decision) = parent.filter(|parent| parent.type_is == Some(NodeType::Decision)) else {
+        comparisons.diagnostics.push(Diagnostic::warning(Some(block.file_order), format!("'{}' should be under the Decision it compares for", block.text)));
+        continue;
+      };
+      for idx in graph.children_of_type(block.file_order, NodeType::Note) {
+        comparisons.read_comparison(decision.file_order, &graph.nodes.nodes[idx as usize]);
+      }
+    }
+    return comparisons;
+  }
+
+  fn read_comparison(&mut self, decision: u32, node: &Node) {
+    let Some((a, b, ratio)) = comparison(&node.text) else {
+      self.diagnostics.push(Diagnostic::warning(Some(node.file_order), format!("'{}' isn't a comparison - ex. Reliable vs Ops burden: 3", node.text)));
+      return;
+    };
+    let (Some(a), Some(b)) = (self.find(decision, node.file_order, &a), self.find(decision, node.file_order, &b)) else { return; };
+    if a.1 != b.1 {
+      self.diagnostics.push(Diagnostic::warning(Some(node.file_order), format!("'{}' compares an Option with a Pro / Con", node.text)));
+      return;
+    }
+    let found = self.by_decision.entry(decision).or_default();
+    if let Some(existing) = found.iter().position(|(_, other)| (other.a, other.b) == (a.0, b.0) || (other.a, other.b) == (b.0, a.0)) {
+      self.diagnostics.push(Diagnostic::warning(Some(node.file_order), format!("'{}' compares the same pair again - the last one is used", node.text)));
+      found.remove(existing);
+    }
+    found.push((a.1, Comparison{a: a.0, b: b.0, ratio}));
+  }
+
+  /// Option or Pro / Con of the Decision with this text
+  fn find(&mut self, decision: u32, file_order: u32, name: &str) -> Option<(u32, ComparedKind)> {
+    let found: Vec<(u32, ComparedKind)> = candidates(self.graph, decision).into_iter()
+      .filter(|(idx, _)| self.graph.nodes.nodes[*idx as usize].text.trim().eq_ignore_ascii_case(name.trim()))
+      .collect();
+    match found[..] {
+      [one] => return Some(one),
+      [] => self.diagnostics.push(Diagnostic::warning(Some(file_order), format!("'{}' isn't an Option or Pro / Con of this Decision", name))),
+      _ => self.diagnostics.push(Diagnostic::warning(Some(file_order), format!("'{}' matches more than one Option or Pro / Con", name))),
+    }
+    return None;
+  }
+
+  /// Priority vector of one kind of a Decision's comparisons - None if a pair is missing
+  fn priority_vector(&mut self, decision: u32, kind: ComparedKind) -> Option<PriorityVector> {
+    let comparisons: Vec<&Comparison> = self.by_decision.get(&decision)?.iter()
+      .filter(|(found_kind, _)| *found_kind == kind)
+      .map(|(_, comparison)| comparison)
+      .collect();
+    let mut items: Vec<u32> = comparisons.iter().flat_map(|comparison| [comparison.a, comparison.b]).collect();
+    items.sort_unstable();
+    items.dedup();
+    if items.len() < 2 { return None; }
+    let position = |idx: u32| items.iter().position(|item| *item == idx).unwrap();
+    let mut matrix = vec![vec![None; items.len()]; items.len()];
+    for comparison in &comparisons {
+      let (a, b) = (position(comparison.a), position(comparison.b));
+      matrix[a][b] = Some(comparison.ratio);
+      matrix[b][a] = Some(1.0 / comparison.ratio);
+    }
+    let mut missing = Vec::new();
+    for a in 0..items.len() {
+      matrix[a][a] = Some(1.0);
+      for b in a + 1..items.len() {
+        if matrix[a][b].is_none() { missing.push((items[a], items[b])); }
+      }
+    }
+    if !missing.is_empty() {
+      let graph = self.graph;
+      let text_of = |idx: u32| graph.nodes.nodes[idx as usize].text.clone();
+      let pairs: Vec<String> = missing.iter().map(|(a, b)| format!("{} vs {}", text_of(*a), text_of(*b))).collect();
+      let decision_text = text_of(decision);
+      self.diagnostics.push(Diagnostic::warning(Some(decision), format!("'{}' is missing comparisons: {}", decision_text, pairs.join(", "))));
+      return None;
+    }
+    let matrix: Vec<Vec<f64>> = matrix.into_iter().map(|row| row.into_iter().map(Option::unwrap).collect()).collect();
+    let (priorities, consistency_ratio) = principal_eigenvector(&matrix);
+    if consistency_ratio > CONSISTENCY_LIMIT {
+      let decision_text = &self.graph.nodes.nodes[decision as usize].text;
+      self.diagnostics.push(Diagnostic::warning(Some(decision), format!("'{}' {} comparisons are inconsistent - consistency ratio {:.2} is over {}",
+        decision_text, if kind == ComparedKind::Criteria { "Pro / Con" } else { "Option" }, consistency_ratio, CONSISTENCY_LIMIT)));
+    }
+    return Some(PriorityVector{
+      kind,
+      priorities: items.iter().zip(priorities).map(|(file_order, priority)| Priority{file_order: *file_order, priority}).collect(),
+      consistency_ratio,
+    });
+  }
+}
+
+fn is_block(node: &Node) -> bool {
+  return node.type_is == Some(NodeType::Note) && tags(&node.text).iter().any(|tag| tag.eq_ignore_ascii_case(AHP_TAG));
+}
+
+/// What a Decision's comparisons can name - its Options & their Pros / Cons
+fn candidates(graph: &Graph, decision: u32) -> Vec<(u32, ComparedKind)> {
+  let options = graph.children_of_type(decision, NodeType::Option);
+  let mut found: Vec<(u32, ComparedKind)> = options.iter()
+    .flat_map(|option| graph.children_of_type(*option, NodeType::Pro).into_iter().chain(graph.children_of_type(*option, NodeType::Con)))
+    .map(|idx| (idx, ComparedKind::Criteria))
+    .collect();
+  found.sort_unstable_by_key(|(idx, _)| *idx);
+  found.dedup(); //< Comparative Pros / Cons are under several Options
+  found.extend(options.into_iter().map(|option| (option, ComparedKind::Options)));
+  return found;
+}
+
+/// Priorities (by power iteration) & consistency ratio of a reciprocal comparison matrix
+fn principal_eigenvector(matrix: &[Vec<f64>]) -> (Vec<f64>, f64) {
+  let size = matrix.len();
+  let multiply = |vector: &[f64]| -> Vec<f64> { matrix.iter().map(|row| row.iter().zip(vector).map(|(a, b)| a * b).sum()).collect() };
+  let mut vector = vec![1.0 / size as f64; size];
+  for _ in 0..1000 {
+    let next = multiply(&vector);
+    let total: f64 = next.iter().sum();
+    let next: Vec<f64> = next.iter().map(|value| value / total).collect();
+    let change: f64 = next.iter().zip(&vector).map(|(a, b)| (a - b).abs()).sum();
+    vector = next;
+    if change < 1e-12 { break; }
+  }
+  let lambda_max = multiply(&vector).iter().zip(&vector).map(|(product, value)| product / value).sum::<f64>() / size as f64;
+  let random_index = RANDOM_INDEX[size.min(RANDOM_INDEX.len() - 1)];
+  let consistency_ratio = if random_index == 0.0 { 0.0 } else { ((lambda_max - size as f64) / (size as f64 - 1.0) / random_index).max(0.0) };
+  return (vector, consistency_ratio);
+}
+
+/// Weights of compared Pros / Cons - their priority scaled so they average 1 like unweighted ones
+pub fn criteria_weights(graph: &Graph) -> Vec<Priority> {
+  let mut comparisons = Comparisons::read(graph);
+  let decisions: Vec<u32> = comparisons.by_decision.keys().copied().collect();
+  return decisions.into_iter()
+    .filter_map(|decision| comparisons.priority_vector(decision, ComparedKind::Criteria))
+    .flat_map(|vector| {
+      let size = vector.priorities.len() as f64;
+      vector.priorities.into_iter().map(move |found| Priority{file_order: found.file_order, priority: found.priority * size})
+    })
+    .collect();
+}
+
+/// Problems in `#ahp` blocks - unknown names, missing pairs & inconsistent comparisons. Also reported by validation
+pub fn check(nodes: &Nodes) -> Vec<Diagnostic> {
+  let graph = Graph::new(nodes);
+  let mut comparisons = Comparisons::read(&graph);
+  let decisions: Vec<u32> = comparisons.by_decision.keys().copied().collect();
+  for decision in decisions {
+    comparisons.priority_vector(decision, ComparedKind::Criteria);
+    comparisons.priority_vector(decision, ComparedKind::Options);
+  }
+  return comparisons.diagnostics;
+}
+
+/// Priorities & Option ranking of every Decision with comparisons
+pub fn prioritize(nodes: &Nodes) -> Vec<DecisionPriorities> {
+  let scores = ScoreModel::new(nodes);
+  let mut comparisons = Comparisons::read(&scores.graph);
+  let decisions: Vec<u32> = comparisons.by_decision.keys().copied().collect();
+  return decisions.into_iter().map(|decision| {
+    let criteria = comparisons.priority_vector(decision, ComparedKind::Criteria);
+    let options = comparisons.priority_vector(decision, ComparedKind::Options);
+    let mut ranking: Vec<RankedOption> = match &options {
+      Some(options) => options.priorities.iter().map(|found| RankedOption{option: found.file_order, score: found.priority}).collect(),
+      None => scores.graph.children_of_type(decision, NodeType::Option).into_iter()
+        .map(|option| RankedOption{option, score: scores.score(option)})
+        .collect(),
+    };
+    ranking.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.option.cmp(&b.option)));
+    DecisionPriorities{decision, criteria, options, ranking}
+  }).collect();
+}
+
+/// Op that records `a` mattering `ratio` times more than `b` for a Decision - rewrites an existing comparison of the
+/// pair, else adds one to the Decision's `#ahp` block (adding the block after its last node if it has none)
+pub fn comparison_op(nodes: &Nodes, decision: u32, a: u32, b: u32, ratio: f64) -> Result<Op, String> {
+  if !(ratio.is_finite() && ratio > 0.0) { return Err(format!("A comparison needs a ratio over 0, not {}", ratio)); }
+  let graph = Graph::new(nodes);
+  let decision_node = nodes.nodes.get(decision as usize).filter(|node| node.type_is == Some(NodeType::Decision))
+    .ok_or(format!("Node {} isn't a Decision", decision))?;
+  let candidates = candidates(&graph, decision);
+  let kind_of = |idx: u32| candidates.iter().find(|(found, _)| *found == idx).map(|(_, kind)| *kind)
+    .ok_or(format!("Node {} isn't an Option or Pro / Con of '{}'", idx, decision_node.text));
+  if kind_of(a)? != kind_of(b)? || a == b { return Err("Can only compare two Options or two Pros / Cons".to_string()); }
+
+  // Written with the one that matters more first, so it reads as a whole number where it can
+  let (more, less, ratio) = if ratio >= 1.0 { (a, b, ratio) } else { (b, a, 1.0 / ratio) };
+  let text = format!("{} vs {}: {}", nodes.nodes[more as usize].text.trim(), nodes.nodes[less as usize].text.trim(), (ratio * 1e6).round() / 1e6);
+  let blocks: Vec<&Node> = graph.children_of_type(decision, NodeType::Note).into_iter()
+    .map(|idx| &nodes.nodes[idx as usize])
+    .filter(|node| is_block(node))
+    .collect();
+  let text_of = |idx: u32| nodes.nodes[idx as usize].text.trim().to_lowercase();
+  let pair = [text_of(a), text_of(b)];
+  for block in &blocks {
+    for idx in graph.children_of_type(block.file_order, NodeType::Note) {
+      let Some((found_a, found_b, _)) = comparison(&nodes.nodes[idx as usize].text) else { continue; };
+      let found = [found_a.trim().to_lowercase(), found_b.trim().to_lowercase()];
+      if found == pair || [&found[1], &found[0]] == [&pair[0], &pair[1]] { return Ok(Op::EditText{file_order: idx, text}); }
+    }
+  }
+
+  let comparison_node = |file_order: u32, parent: u32, level: u32| Node::new(text.clone(), NodeType::Note, file_order, level, vec![parent], Vec::new());
+  if let Some(block) = blocks.last() {
+    let at = end_of_subtree(nodes, block.file_order);
+    return Ok(Op::InsertNode{node: comparison_node(at, block.file_order, block.level + 1)});
+  }
+  let at = end_of_subtree(nodes, decision);
+  let block = Node::new("Pairwise comparisons #ahp".to_string(), NodeType::Note, at, decision_node.level + 1, vec![decision], Vec::new());
+  return Ok(Op::Batch{ops: vec![Op::InsertNode{node: block}, Op::InsertNode{node: comparison_node(at + 1, at, decision_node.level + 2)}]});
+}
+
+/// File order just past a node's last nested node
+fn end_of_subtree(nodes: &Nodes, idx: u32) -> u32 {
+  let level = nodes.nodes[idx as usize].level;
+  return nodes.nodes[idx as usize + 1..].iter().find(|node| node.level <= level).map_or(nodes.nodes.len() as u32, |node| node.file_order);
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::file_parse::{DATA_DIR, parse_file, parse_str};
+  use crate::file_write::nodes_to_string;
+  use crate::history::apply;
+  use std::path::Path;
+
+  fn priorities_of(vector: &Option<PriorityVector>) -> Vec<(u32, f64)> {
+    return vector.as_ref().unwrap().priorities.iter().map(|found| (found.file_order, (found.priority * 1000.0).round() / 1000.0)).collect();
+  }
+
+  #[test]
+  fn test_prioritize() {
+    let nodes = parse_file(DATA_DIR.join("09_pairwise_comparisons.md")).unwrap();
+    assert!(check(&nodes).is_empty(), "{:?}", check(&nodes));
+    let found = prioritize(&nodes);
+    assert_eq!(found.len(), 2);
+
+    let database = &found[0];
+    assert_eq!(priorities_of(&database.criteria), vec![(2, 0.648), (3, 0.23), (5, 0.122)]);
+    assert!((database.criteria.as_ref().unwrap().consistency_ratio - 0.003).abs() < 0.001);
+    // Postgres: reliable - ops burden / SQLite: simple to run - the criteria weights average 1
+    let ranking: Vec<u32> = database.ranking.iter().map(|ranked| ranked.option).collect();
+    assert_eq!(ranking, vec![1, 4]);
+    assert!((database.ranking[0].score - (0.648 - 0.23) * 3.0).abs() < 0.01, "{:?}", database.ranking);
+
+    let host = &found[1];
+    assert!(host.criteria.is_none());
+    assert_eq!(priorities_of(&host.options), vec![(11, 0.286), (12, 0.143), (13, 0.571)]);
+    assert!(host.options.as_ref().unwrap().consistency_ratio < 1e-9);
+    let ranking: Vec<u32> = host.ranking.iter().map(|ranked| ranked.option).collect();
+    assert_eq!(ranking, vec![13, 11, 12]);
+  }
+
+  #[test]
+  fn test_check() {
+    let nodes = parse_str("# X (md-decision-trees)\n\nD: Which host?\n* O: Fly\n* O: Hetzner\n* O: AWS\n* N: Pairwise comparisons #ahp\n  \
+      * N: Fly vs Hetzner: 9\n  * N: Hetzner vs AWS: 9\n  * N: AWS vs Fly: 9\n  * N: Fly vs Azure: 2\n  * N: Fly is better").unwrap();
+    let messages: Vec<String> = check(&nodes).into_iter().map(|diagnostic| diagnostic.message).collect();
+    assert_eq!(messages.len(), 3, "{:?}", messages);
+    assert!(messages[0].contains("'Azure' isn't an Option"));
+    assert!(messages[1].contains("isn't a comparison"));
+    assert!(messages[2].contains("inconsistent"));
+  }
+
+  #[test]
+  fn test_comparison_op() {
+    let mut nodes = parse_str("# X (md-decision-trees)\n\nD: Which host?\n* O: Fly\n* O: Hetzner\n\nD: Next").unwrap();
+    let op = comparison_op(&nodes, 0, 1, 2, 0.5).unwrap();
+    apply(&mut nodes, &op).unwrap();
+    assert_eq!(nodes_to_string(&nodes, Path::new("x.md")).unwrap(),
+      "# X (md-decision-trees)\n\nD: Which host?\n* O: Fly\n* O: Hetzner\n* N: Pairwise comparisons #ahp\n  * N: Hetzner vs Fly: 2\n\nD: Next");
+    let op = comparison_op(&nodes, 0, 1, 2, 3.0).unwrap();
+    apply(&mut nodes, &op).unwrap();
+    assert_eq!(nodes.nodes[4].text, "Fly vs Hetzner: 3");
+    assert!(comparison_op(&nodes, 0, 1, 1, 3.0).is_err());
+    assert!(comparison_op(&nodes, 0, 1, 5, 3.0).is_err());
+  }
+}
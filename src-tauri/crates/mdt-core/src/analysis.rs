@@ -0,0 +1,221 @@
+use crate::graph::Graph;
+use crate::node_meta::{payoff, probability};
+use crate::structs::{Nodes, NodeType};
+use crate::validate::{Diagnostic, Severity};
+use serde::{Serialize, Deserialize};
+
+/// How far a Chance node's Outcome probabilities may add up from 1 - ex. thirds written as `p=0.333`
+pub const PROBABILITY_TOLERANCE: f64 = 0.001;
+
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct NodeValue {
+  pub file_order: u32,
+  pub expected_value: f64,
+}
+
+/// A top level Decision rolled back - values of every Decision / Option / Chance / Outcome under it
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct DecisionAnalysis {
+  pub decision: u32,
+  pub expected_value: f64,
+  pub best_option: Option<u32>,
+  pub optimal_path: Vec<u32>, //< Best Option of this Decision & of every Decision reached after it, in file order
+  pub values: Vec<NodeValue>, //< In file order
+}
+
+/// Probabilities & payoffs read from each node's text - kept apart from the nodes so they can be changed & rolled back
+/// again, ex. for sensitivity analysis
+pub struct Model<'a> {
+  pub graph: Graph<'a>,
+  pub probabilities: Vec<Option<f64>>,
+  pub payoffs: Vec<Option<f64>>,
+}
+
+impl<'a> Model<'a> {
+  pub fn new(nodes: &'a Nodes) -> Self {
+    return Model{
+      graph: Graph::new(nodes),
+      probabilities: nodes.nodes.iter().map(|node| probability(&node.text)).collect(),
+      payoffs: nodes.nodes.iter().map(|node| payoff(&node.text)).collect(),
+    };
+  }
+
+  fn type_of(&self, idx: u32) -> Option<&NodeType> {
+    return self.graph.nodes.nodes.get(idx as usize)?.type_is.as_ref();
+  }
+
+  /// Children that carry value on - Decisions & Chance nodes after an Option / Outcome
+  fn continuations(&self, idx: u32) -> Vec<u32> {
+    let mut found = self.graph.children_of_type(idx, NodeType::Decision);
+    found.extend(self.graph.children_of_type(idx, NodeType::Chance));
+    found.sort_unstable();
+    return found;
+  }
+
+  /// Problems that stop the tree being rolled back - also reported by validation
+  pub fn check(&self) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for node in &self.graph.nodes.nodes {
+      let idx = node.file_order;
+      match node.type_is {
+        Some(NodeType::Chance) => {
+          let outcomes = self.graph.children_of_type(idx, NodeType::Outcome);
+          if outcomes.is_empty() {
+            diagnostics.push(Diagnostic::error(Some(idx), format!("'{}' is a Chance node without Outcomes", node.text)));
+            continue;
+          }
+          let probabilities: Option<Vec<f64>> = outcomes.iter().map(|outcome| self.probabilities[*outcome as usize]).collect();
+          let Some(probabilities) = probabilities else { continue; }; //< Each missing one is reported on its Outcome
+          let total: f64 = probabilities.iter().sum();
+          if (total - 1.0).abs() > PROBABILITY_TOLERANCE {
+            diagnostics.push(Diagnostic::error(Some(idx), format!("'{}' Outcome probabilities add up to {} instead of 1", node.text, total)));
+          }
+        },
+        Some(NodeType::Outcome) => {
+          match self.probabilities[idx as usize] {
+            None => diagnostics.push(Diagnostic::error(Some(idx), format!("'{}' is an Outcome without a probability - ex. p=0.5", node.text))),
+            Some(probability) if probability > 1.0 => diagnostics.push(Diagnostic::error(Some(idx),
+              format!("'{}' has a probability of {}, over 1", node.text, probability))),
+            _ => {},
+          }
+          let parent_type = node.parent_idxs.first().and_then(|parent| self.type_of(*parent));
+          if parent_type != Some(&NodeType::Chance) {
+            diagnostics.push(Diagnostic::warning(Some(idx), format!("'{}' is an Outcome that isn't under a Chance node", node.text)));
+          }
+        },
+        _ => {},
+      }
+    }
+    return diagnostics;
+  }
+
+  /// Expected value of a node - Decisions take their best Option, Chance nodes weigh their Outcomes by probability, &
+  /// Options / Outcomes add their own payoff to the Decisions & Chance nodes after them
+  pub fn expected_value(&self, idx: u32) -> f64 {
+    return self.roll_back(idx, &mut Vec::new());
+  }
+
+  fn roll_back(&self, idx: u32, values: &mut Vec<NodeValue>) -> f64 {
+    let own_payoff = self.payoffs.get(idx as usize).copied().flatten().unwrap_or(0.0);
+    let expected_value = match self.type_of(idx) {
+      Some(NodeType::Decision) => {
+        let options = self.graph.children_of_type(idx, NodeType::Option);
+        let option_values: Vec<f64> = options.iter().map(|option| self.roll_back(*option, values)).collect();
+        option_values.into_iter().reduce(f64::max).unwrap_or(own_payoff)
+      },
+      Some(NodeType::Chance) => {
+        let outcomes = self.graph.children_of_type(idx, NodeType::Outcome);
+        outcomes.iter().map(|outcome| self.probabilities[*outcome as usize].unwrap_or(0.0) * self.roll_back(*outcome, values)).sum()
+      },
+      _ => own_payoff + self.continuations(idx).iter().map(|next| self.roll_back(*next, values)).sum::<f64>(),
+    };
+    values.push(NodeValue{file_order: idx, expected_value});
+    return expected_value;
+  }
+
+  /// Option with the highest expected value - the first one on a tie
+  pub fn best_option(&self, decision: u32) -> Option<u32> {
+    let options = self.graph.children_of_type(decision, NodeType::Option);
+    return options.into_iter()
+      .map(|option| (option, self.expected_value(option)))
+      .fold(None, |best: Option<(u32, f64)>, (option, value)| match best {
+        Some((_, best_value)) if best_value >= value => best,
+        _ => Some((option, value)),
+      })
+      .map(|(option, _)| option);
+  }
+
+  fn add_optimal_path(&self, idx: u32, path: &mut Vec<u32>) {
+    let nexts = match self.type_of(idx) {
+      Some(NodeType::Decision) => {
+        let Some(best) = self.best_option(idx) else { return; };
+        path.push(best);
+        vec![best]
+      },
+      Some(NodeType::Chance) => self.graph.children_of_type(idx, NodeType::Outcome),
+      _ => self.continuations(idx),
+    };
+    for next in nexts { self.add_optimal_path(next, path); }
+  }
+
+  pub fn analyze_decision(&self, decision: u32) -> DecisionAnalysis {
+    let mut values = Vec::new();
+    let expected_value = self.roll_back(decision, &mut values);
+    values.sort_by_key(|value| value.file_order);
+    values.dedup_by_key(|value| value.file_order);
+    let mut optimal_path = Vec::new();
+    self.add_optimal_path(decision, &mut optimal_path);
+    optimal_path.sort_unstable();
+    return DecisionAnalysis{decision, expected_value, best_option: self.best_option(decision), optimal_path, values};
+  }
+
+  /// Top level Decisions with a Chance node or payoff under them - plain Pro / Con trees have nothing to roll back
+  pub fn analyzed_decisions(&self) -> Vec<u32> {
+    let nodes = &self.graph.nodes.nodes;
+    return nodes.iter()
+      .filter(|node| node.type_is == Some(NodeType::Decision) && node.parent_idxs.is_empty())
+      .filter(|decision| {
+        let mut values = Vec::new();
+        self.roll_back(decision.file_order, &mut values);
+        values.iter().any(|value| self.type_of(value.file_order) == Some(&NodeType::Chance) || self.payoffs[value.file_order as usize].is_some())
+      })
+      .map(|decision| decision.file_order)
+      .collect();
+  }
+}
+
+/// Roll back every top level Decision with analysis data - errors if any Chance node's probabilities are off
+pub fn analyze(nodes: &Nodes) -> Result<Vec<DecisionAnalysis>, String> {
+  let model = Model::new(nodes);
+  let errors: Vec<String> = model.check().into_iter().filter(|diagnostic| diagnostic.severity == Severity::Error)
+    .map(|diagnostic| diagnostic.message)
+    .collect();
+  if !errors.is_empty() { return Err(errors.join("\n")); }
+  return Ok(model.analyzed_decisions().into_iter().map(|decision| model.analyze_decision(decision)).collect());
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::file_parse::{DATA_DIR, parse_file, parse_str};
+
+  #[test]
+  fn test_analyze() {
+    let nodes = parse_file(DATA_DIR.join("08_decision_analysis.md")).unwrap();
+    let analyses = analyze(&nodes).unwrap();
+    assert_eq!(analyses.len(), 1);
+    let analysis = &analyses[0];
+    let value_of = |file_order: u32| analysis.values.iter().find(|value| value.file_order == file_order).unwrap().expected_value;
+    // Build: -50 + (0.6 * 200 + 0.4 * 20) = 78 / Buy: -30 + 0.5 * (vendor improves: best of 120 / 90) + 0.5 * 60 = 60
+    assert!((value_of(1) - 78.0).abs() < 1e-9, "{}", value_of(1));
+    assert!((value_of(5) - 60.0).abs() < 1e-9, "{}", value_of(5));
+    assert_eq!(analysis.best_option, Some(1));
+    assert!((analysis.expected_value - 78.0).abs() < 1e-9);
+    assert_eq!(analysis.optimal_path, vec![1]);
+    // The later Decision's best Option is found too
+    assert_eq!(Model::new(&nodes).best_option(8), Some(9));
+  }
+
+  #[test]
+  fn test_probabilities_must_add_up() {
+    let nodes = parse_str("# X (md-decision-trees)\n\nD: Launch?\n* O: Yes\n  * X: Demand\n    * Y: High p=0.5 value=10\n    \
+      * Y: Low p=0.3 value=1\n    * Y: None\n* O: No value=0").unwrap();
+    let messages: Vec<String> = Model::new(&nodes).check().into_iter().map(|diagnostic| diagnostic.message).collect();
+    assert_eq!(messages.len(), 1, "{:?}", messages);
+    assert!(messages[0].contains("without a probability"));
+    assert!(analyze(&nodes).is_err());
+
+    let nodes = parse_str("# X (md-decision-trees)\n\nD: Launch?\n* O: Yes\n  * X: Demand\n    * Y: High p=50% value=10\n    \
+      * Y: Low p=30% value=1\n* O: No value=0").unwrap();
+    let err = analyze(&nodes).unwrap_err();
+    assert!(err.contains("add up to 0.8"), "{}", err);
+  }
+
+  #[test]
+  fn test_plain_trees_are_skipped() {
+    let nodes = parse_file(DATA_DIR.join("03_basic_encoding.md")).unwrap();
+    assert!(analyze(&nodes).unwrap().is_empty());
+  }
+}
@@ -0,0 +1,324 @@
+use crate::file_write::SaveOptions;
+use crate::structs::NodeType;
+use serde::{Serialize, Deserialize};
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs::read_to_string;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// Found by searching up from a decision file's directory - the closest one wins, there is no merging
+pub const CONFIG_FILE_NAME: &str = ".mdt.toml";
+
+/// How a validation rule is reported
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[serde(rename_all = "lowercase")]
+pub enum RuleLevel { Off, Warning, Error }
+
+/// A node type's `[node_types.<type>]` table - unset fields keep the built in format
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[serde(default, deny_unknown_fields)]
+pub struct NodeTypeConfig {
+  pub token: Option<String>, //< Written before the `:` - ex. `D` in `D: Which database?`
+  pub color: Option<String>, //< Any CSS color - None for the app's own
+  pub allowed: bool, //< Nodes of disallowed types are reported by validation
+}
+
+impl Default for NodeTypeConfig {
+  fn default() -> Self { NodeTypeConfig{token: None, color: None, allowed: true} }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[serde(default, deny_unknown_fields, rename_all = "lowercase")]
+pub struct NodeTypesConfig {
+  pub decision: NodeTypeConfig,
+  pub option: NodeTypeConfig,
+  pub pro: NodeTypeConfig,
+  pub con: NodeTypeConfig,
+  pub note: NodeTypeConfig,
+  pub assumption: NodeTypeConfig,
+  pub risk: NodeTypeConfig,
+  pub chance: NodeTypeConfig,
+  pub outcome: NodeTypeConfig,
+}
+
+impl NodeTypesConfig {
+  /// None for custom types - they have their own `CustomNodeType`
+  pub fn get(&self, node_type: &NodeType) -> Option<&NodeTypeConfig> {
+    return match node_type {
+      NodeType::Decision => Some(&self.decision), NodeType::Option => Some(&self.option),
+      NodeType::Pro => Some(&self.pro), NodeType::Con => Some(&self.con),
+      NodeType::Note => Some(&self.note),
+      NodeType::Assumption => Some(&self.assumption), NodeType::Risk => Some(&self.risk),
+      NodeType::Chance => Some(&self.chance), NodeType::Outcome => Some(&self.outcome),
+      NodeType::Custom(_) => None,
+    };
+  }
+}
+
+/// A project's own node type - `[custom_types.<id>]`, ex. `[custom_types.question]` is parsed as `NodeType::Custom("question")`
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[serde(deny_unknown_fields)]
+pub struct CustomNodeType {
+  pub token: String,
+  pub name: Option<String>, //< Shown in the UI - the id if unset
+  pub color: Option<String>,
+  #[serde(default)]
+  pub parents: Vec<String>, //< Ids of the types it can be under - ex. `["decision", "option"]`. Empty for any
+}
+
+/// Rules `validate` checks on top of broken links, which are always errors
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[serde(default, deny_unknown_fields)]
+pub struct ValidationRules {
+  pub non_option_link: RuleLevel, //< A Pro / Con linked to something other than an Option
+  pub decision_without_options: RuleLevel,
+  pub max_depth: Option<u32>, //< Deepest bullet level allowed - a warning past it
+  pub custom_type_parent: RuleLevel, //< A custom type under a type that isn't in its `parents`
+  pub assumption_risk_parent: RuleLevel, //< An Assumption / Risk that isn't under a Decision or Option
+}
+
+impl Default for ValidationRules {
+  fn default() -> Self {
+    ValidationRules{
+      non_option_link: RuleLevel::Warning, decision_without_options: RuleLevel::Off, max_depth: None,
+      custom_type_parent: RuleLevel::Warning, assumption_risk_parent: RuleLevel::Warning,
+    }
+  }
+}
+
+/// A project's `.mdt.toml` - every field is optional, so an empty file is the built in format
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+  pub indent_width: u32, //< Spaces per bullet level
+  pub node_types: NodeTypesConfig,
+  pub custom_types: BTreeMap<String, CustomNodeType>,
+  pub validation: ValidationRules,
+  pub save: SaveOptions, //< Defaults until the app's own settings are changed
+}
+
+impl Default for Config {
+  fn default() -> Self {
+    Config{
+      indent_width: 2, node_types: NodeTypesConfig::default(), custom_types: BTreeMap::new(),
+      validation: ValidationRules::default(), save: SaveOptions::default(),
+    }
+  }
+}
+
+const BUILT_IN_TYPES: [NodeType; 9] = [
+  NodeType::Decision, NodeType::Option, NodeType::Pro, NodeType::Con, NodeType::Note, NodeType::Assumption, NodeType::Risk,
+  NodeType::Chance, NodeType::Outcome,
+];
+
+impl Config {
+  /// Config for a decision file - the closest `.mdt.toml` above it, or the built in format if there is none
+  pub fn discover(file_path: &Path) -> Result<Config, Box<dyn Error>> {
+    return match find_config_file(file_path) {
+      Some(config_path) => Config::load(&config_path),
+      None => Ok(Config::default()),
+    };
+  }
+
+  pub fn load(config_path: &Path) -> Result<Config, Box<dyn Error>> {
+    let content = read_to_string(config_path)?;
+    return Config::from_toml(&content).map_err(|err| format!("{}: {}", config_path.display(), err).into());
+  }
+
+  pub fn from_toml(content: &str) -> Result<Config, Box<dyn Error>> {
+    let config: Config = toml::from_str(content)?;
+    config.check()?;
+    return Ok(config);
+  }
+
+  /// Tokens have to stay unambiguous for the parser - ex. a `,` or `:` would be read as part of the encoding
+  fn check(&self) -> Result<(), String> {
+    if self.indent_width == 0 { Err("indent_width must be at least 1")? }
+    for (id, custom_type) in &self.custom_types {
+      if id.is_empty() || !id.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_') {
+        Err(format!("Custom type id '{}' can only have lowercase letters, numbers & '_'", id))?
+      }
+      if BUILT_IN_TYPES.iter().any(|node_type| node_type.id() == id) {
+        Err(format!("Custom type '{}' has the same id as a built in type - use [node_types.{}] to change it", id, id))?
+      }
+      if let Some(parent) = custom_type.parents.iter().find(|parent| self.node_type_by_id(parent).is_none()) {
+        Err(format!("Custom type '{}' has an unknown parent type '{}'", id, parent))?
+      }
+    }
+    let node_types = self.node_types();
+    for node_type in &node_types {
+      let token = self.token(node_type);
+      if token.is_empty() || token.chars().all(|c| c.is_ascii_digit())
+        || token.chars().any(|c| c.is_whitespace() || [':', ',', '-', '*'].contains(&c)) {
+        Err(format!("Token '{}' for {} can't be empty, a number or contain whitespace, ':', ',', '-' or '*'", token, node_type.id()))?
+      }
+      if let Some(other) = node_types.iter().find(|other| *other != node_type && self.token(other) == token) {
+        Err(format!("{} & {} both use the token '{}'", node_type.id(), other.id(), token))?
+      }
+    }
+    return Ok(());
+  }
+
+  /// Built in types, then the project's custom types
+  pub fn node_types(&self) -> Vec<NodeType> {
+    return BUILT_IN_TYPES.into_iter().chain(self.custom_types.keys().map(|id| NodeType::Custom(id.clone()))).collect();
+  }
+
+  /// Token a node type is written with - a custom type missing from the config is written with its id
+  pub fn token<'a>(&'a self, node_type: &'a NodeType) -> &'a str {
+    if let Some(token) = self.node_types.get(node_type).and_then(|config| config.token.as_ref()) { return token; }
+    return match node_type {
+      NodeType::Decision => "D", NodeType::Option => "O",
+      NodeType::Pro => "P", NodeType::Con => "C",
+      NodeType::Note => "N",
+      NodeType::Assumption => "A", NodeType::Risk => "R",
+      NodeType::Chance => "X", NodeType::Outcome => "Y",
+      NodeType::Custom(id) => self.custom_types.get(id).map_or(id, |custom_type| &custom_type.token),
+    };
+  }
+
+  pub fn node_type(&self, token: &str) -> Option<NodeType> {
+    return self.node_types().into_iter().find(|node_type| self.token(node_type) == token);
+  }
+
+  pub fn node_type_by_id(&self, id: &str) -> Option<NodeType> {
+    return self.node_types().into_iter().find(|node_type| node_type.id() == id);
+  }
+
+  /// Name shown to users - ex. `Decision`, or a custom type's `name`
+  pub fn type_name(&self, node_type: &NodeType) -> String {
+    return match node_type {
+      NodeType::Custom(id) => self.custom_types.get(id).and_then(|custom_type| custom_type.name.clone()).unwrap_or(id.clone()),
+      _ => format!("{:?}", node_type),
+    };
+  }
+
+  /// Disallowed built in types & custom types missing from the config are reported by validation
+  pub fn is_allowed(&self, node_type: &NodeType) -> bool {
+    return match node_type {
+      NodeType::Custom(id) => self.custom_types.contains_key(id),
+      _ => self.node_types.get(node_type).is_none_or(|config| config.allowed),
+    };
+  }
+
+  /// Matches a comparative type token - ex. `P,1,4-C,2`. Groups are (type, indexes, other type, other indexes)
+  pub fn comparative_regex(&self) -> Regex {
+    let types = format!("({}|{})", regex::escape(self.token(&NodeType::Pro)), regex::escape(self.token(&NodeType::Con)));
+    return Regex::new(&format!(r"^{types},([0-9]+(?:,[0-9]+)*)(?:-{types},([0-9]+(?:,[0-9]+)*))?$", types=types)).unwrap();
+  }
+}
+
+/// Closest `.mdt.toml` in the file's directory or any above it
+pub fn find_config_file(file_path: &Path) -> Option<PathBuf> {
+  return file_path.ancestors().skip(1)
+    .map(|dir| dir.join(CONFIG_FILE_NAME))
+    .find(|config_path| match config_path.metadata() {
+      Ok(metadata) => metadata.is_file(),
+      Err(err) => err.kind() != ErrorKind::NotFound, //< Found but unreadable - reported when loaded
+    });
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_utils::scratch_dir;
+  use std::fs;
+
+  #[test]
+  fn test_empty_is_default() {
+    assert_eq!(Config::from_toml("").unwrap(), Config::default());
+    assert_eq!(Config::default().token(&NodeType::Pro), "P");
+    assert_eq!(Config::default().node_type("N"), Some(NodeType::Note));
+  }
+
+  #[test]
+  fn test_from_toml() {
+    let config = Config::from_toml(r##"
+      indent_width = 4
+      [node_types.pro]
+      token = "+"
+      color = "#00ff00"
+      [node_types.note]
+      allowed = false
+      [validation]
+      decision_without_options = "error"
+      max_depth = 3
+      [save]
+      num_backups = 2
+    "##).unwrap();
+    assert_eq!(config.indent_width, 4);
+    assert_eq!(config.token(&NodeType::Pro), "+");
+    assert_eq!(config.token(&NodeType::Con), "C");
+    assert_eq!(config.node_types.pro.color.as_deref(), Some("#00ff00"));
+    assert!(!config.node_types.note.allowed && config.node_types.decision.allowed);
+    assert_eq!(config.validation.decision_without_options, RuleLevel::Error);
+    assert_eq!(config.validation.non_option_link, RuleLevel::Warning);
+    assert_eq!(config.save.num_backups, 2);
+
+    let caps = config.comparative_regex().captures("+,1-C,2").unwrap();
+    assert_eq!((&caps[1], &caps[2], &caps[3], &caps[4]), ("+", "1", "C", "2"));
+    assert!(config.comparative_regex().captures("P,1").is_none());
+  }
+
+  #[test]
+  fn test_bad_configs() {
+    assert!(Config::from_toml("indent_width = 0").is_err());
+    assert!(Config::from_toml("indent = 2").is_err()); //< Typos aren't silently ignored
+    assert!(Config::from_toml("[node_types.pro]\ntoken = \"P,\"").is_err());
+    assert!(Config::from_toml("[node_types.pro]\ntoken = \"12\"").is_err());
+    let err = Config::from_toml("[node_types.pro]\ntoken = \"C\"").unwrap_err();
+    assert!(err.to_string().contains("both use the token 'C'"), "{}", err);
+    assert!(Config::from_toml("[custom_types.question]\ntoken = \"N\"").is_err());
+    assert!(Config::from_toml("[custom_types.note]\ntoken = \"NT\"").is_err());
+    assert!(Config::from_toml("[custom_types.Question]\ntoken = \"Q\"").is_err());
+    assert!(Config::from_toml("[custom_types.question]\ntoken = \"Q\"\nparents = [\"choice\"]").is_err());
+  }
+
+  #[test]
+  fn test_custom_types() {
+    let config = Config::from_toml(r##"
+      [custom_types.question]
+      token = "Q"
+      name = "Question"
+      color = "#ff9800"
+      parents = ["option", "stakeholder"]
+      [custom_types.stakeholder]
+      token = "S"
+    "##).unwrap();
+    let question = NodeType::Custom("question".to_string());
+    assert_eq!(config.node_type("Q"), Some(question.clone()));
+    assert_eq!(config.token(&question), "Q");
+    assert_eq!(config.type_name(&question), "Question");
+    assert_eq!(config.type_name(&NodeType::Custom("stakeholder".to_string())), "stakeholder");
+    assert_eq!(config.node_type_by_id("stakeholder"), Some(NodeType::Custom("stakeholder".to_string())));
+    assert_eq!(config.node_types().len(), 11);
+    assert!(config.is_allowed(&question));
+    assert!(!config.is_allowed(&NodeType::Custom("cost".to_string())));
+  }
+
+  #[test]
+  fn test_discover() {
+    let dir = scratch_dir("config", "discover");
+    fs::create_dir_all(dir.join("docs/decisions")).unwrap();
+    let file_path = dir.join("docs/decisions/db.md");
+    assert_eq!(find_config_file(&file_path), None); //< Unless the OS temp dir has one above it
+    assert_eq!(Config::discover(&file_path).unwrap(), Config::default());
+
+    fs::write(dir.join(CONFIG_FILE_NAME), "indent_width = 4").unwrap();
+    assert_eq!(Config::discover(&file_path).unwrap().indent_width, 4);
+    fs::write(dir.join("docs").join(CONFIG_FILE_NAME), "indent_width = 3").unwrap();
+    assert_eq!(Config::discover(&file_path).unwrap().indent_width, 3); //< Closest wins
+
+    fs::write(dir.join("docs").join(CONFIG_FILE_NAME), "indent_width = \"3\"").unwrap();
+    let err = Config::discover(&file_path).unwrap_err().to_string();
+    assert!(err.contains(CONFIG_FILE_NAME), "{}", err);
+  }
+}
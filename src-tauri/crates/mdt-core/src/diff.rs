@@ -0,0 +1,246 @@
+use crate::structs::{Node, Nodes, NodeType};
+use serde::{Serialize, Deserialize};
+use std::collections::{HashMap, VecDeque};
+
+/// Nodes with edited text still match if they are at least this similar (0 to 1)
+pub const MIN_TEXT_SIMILARITY: f64 = 0.6;
+
+/// A link from a node to one of its parents - diff_type for Pro / Con links of the opposite type
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct LinkRef {
+  pub file_order: u32,
+  pub diff_type: bool,
+}
+
+/// Old file orders refer to the old nodes, new file orders to the new nodes
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub enum NodeChange {
+  Added { new_file_order: u32, text: String },
+  Removed { old_file_order: u32, text: String },
+  /// Now under a different parent - parents are None for top level nodes
+  Moved { old_file_order: u32, new_file_order: u32, old_parent: Option<u32>, new_parent: Option<u32> },
+  Retyped { old_file_order: u32, new_file_order: u32, old_type: Option<NodeType>, new_type: Option<NodeType> },
+  TextEdited { old_file_order: u32, new_file_order: u32, old_text: String, new_text: String },
+  /// Comparative links changed - added links are new file orders, removed links are old file orders
+  LinksChanged { old_file_order: u32, new_file_order: u32, added: Vec<LinkRef>, removed: Vec<LinkRef> },
+}
+
+impl NodeChange {
+  /// One line description - ex. for the command line
+  pub fn describe(&self, old: &Nodes, new: &Nodes) -> String {
+    let old_text = |idx: &u32| old.nodes.get(*idx as usize).map_or(String::from("?"), |node| node.text.clone());
+    let new_text = |idx: &u32| new.nodes.get(*idx as usize).map_or(String::from("?"), |node| node.text.clone());
+    let describe_links = |links: &Vec<LinkRef>, text_of: &dyn Fn(&u32) -> String| links.iter()
+      .map(|link| format!("{}'{}'", if link.diff_type { "opposite " } else { "" }, text_of(&link.file_order)))
+      .collect::<Vec<String>>().join(", ");
+    match self {
+      NodeChange::Added{text, ..} => format!("+ '{}'", text),
+      NodeChange::Removed{text, ..} => format!("- '{}'", text),
+      NodeChange::Moved{new_file_order, old_parent, new_parent, ..} => format!("~ '{}' moved from {} to {}",
+        new_text(new_file_order),
+        old_parent.as_ref().map_or(String::from("the top level"), |idx| format!("'{}'", old_text(idx))),
+        new_parent.as_ref().map_or(String::from("the top level"), |idx| format!("'{}'", new_text(idx)))),
+      NodeChange::Retyped{new_file_order, old_type, new_type, ..} => format!("~ '{}' changed from {:?} to {:?}", new_text(new_file_order), old_type, new_type),
+      NodeChange::TextEdited{old_text, new_text, ..} => format!("~ '{}' edited to '{}'", old_text, new_text),
+      NodeChange::LinksChanged{new_file_order, added, removed, ..} => format!("~ '{}' links added [{}] removed [{}]",
+        new_text(new_file_order), describe_links(added, &new_text), describe_links(removed, &old_text)),
+    }
+  }
+}
+
+/// Match each old node to a new node - by identical type & text, then identical text, then similar text
+pub fn match_nodes(old: &Nodes, new: &Nodes) -> Vec<Option<u32>> {
+  let mut old_to_new: Vec<Option<u32>> = vec![None; old.nodes.len()];
+  let mut new_matched = vec![false; new.nodes.len()];
+
+  // Identical keys are paired up in file order so duplicates still line up
+  let mut match_exact = |key_of: &dyn Fn(&Node) -> String, old_to_new: &mut Vec<Option<u32>>| {
+    let mut unmatched_new: HashMap<String, VecDeque<usize>> = HashMap::new();
+    for (new_idx, node) in new.nodes.iter().enumerate().filter(|(new_idx, _)| !new_matched[*new_idx]) {
+      unmatched_new.entry(key_of(node)).or_default().push_back(new_idx);
+    }
+    for (old_idx, node) in old.nodes.iter().enumerate() {
+      if old_to_new[old_idx].is_some() { continue; }
+      if let Some(new_idx) = unmatched_new.get_mut(&key_of(node)).and_then(|queue| queue.pop_front()) {
+        old_to_new[old_idx] = Some(new_idx as u32);
+        new_matched[new_idx] = true;
+      }
+    }
+  };
+  match_exact(&|node| format!("{:?}:{}", node.type_is, node.text), &mut old_to_new);
+  match_exact(&|node| node.text.clone(), &mut old_to_new);
+
+  // Best remaining pairs first
+  let mut candidates: Vec<(f64, usize, usize)> = Vec::new();
+  for (old_idx, old_node) in old.nodes.iter().enumerate().filter(|(old_idx, _)| old_to_new[*old_idx].is_none()) {
+    for (new_idx, new_node) in new.nodes.iter().enumerate().filter(|(new_idx, _)| !new_matched[*new_idx]) {
+      let similarity = text_similarity(&old_node.text, &new_node.text);
+      if similarity >= MIN_TEXT_SIMILARITY { candidates.push((similarity, old_idx, new_idx)); }
+    }
+  }
+  candidates.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+  for (_, old_idx, new_idx) in candidates {
+    if old_to_new[old_idx].is_none() && !new_matched[new_idx] {
+      old_to_new[old_idx] = Some(new_idx as u32);
+      new_matched[new_idx] = true;
+    }
+  }
+  return old_to_new;
+}
+
+/// 1 - normalized Levenshtein distance over chars, case-insensitive
+pub fn text_similarity(a: &str, b: &str) -> f64 {
+  let a: Vec<char> = a.to_lowercase().chars().collect();
+  let b: Vec<char> = b.to_lowercase().chars().collect();
+  let max_len = a.len().max(b.len());
+  if max_len == 0 { return 1.0; }
+  let mut prev_row: Vec<usize> = (0..=b.len()).collect();
+  for (i, a_char) in a.iter().enumerate() {
+    let mut row = vec![i + 1; b.len() + 1];
+    for (j, b_char) in b.iter().enumerate() {
+      let substitution = prev_row[j] + if a_char == b_char { 0 } else { 1 };
+      row[j + 1] = substitution.min(prev_row[j + 1] + 1).min(row[j] + 1);
+    }
+    prev_row = row;
+  }
+  return 1.0 - prev_row[b.len()] as f64 / max_len as f64;
+}
+
+/// Links of a node mapped through the matching - links to removed nodes map to None
+fn links(node: &Node, map: &dyn Fn(u32) -> Option<u32>) -> Vec<Option<LinkRef>> {
+  let same = node.parent_idxs.iter().map(|idx| map(*idx).map(|file_order| LinkRef{file_order, diff_type: false}));
+  let diff = node.parent_idxs_diff_type.iter().map(|idx| map(*idx).map(|file_order| LinkRef{file_order, diff_type: true}));
+  return same.chain(diff).collect();
+}
+
+/// What changed between two versions of a file
+pub fn diff(old: &Nodes, new: &Nodes) -> Vec<NodeChange> {
+  let old_to_new = match_nodes(old, new);
+  let mut new_to_old: Vec<Option<u32>> = vec![None; new.nodes.len()];
+  for (old_idx, new_idx) in old_to_new.iter().enumerate() {
+    if let Some(new_idx) = new_idx { new_to_old[*new_idx as usize] = Some(old_idx as u32); }
+  }
+
+  let mut changes = Vec::new();
+  for (new_idx, new_node) in new.nodes.iter().enumerate() {
+    let new_file_order = new_idx as u32;
+    let Some(old_file_order) = new_to_old[new_idx] else {
+      changes.push(NodeChange::Added{new_file_order, text: new_node.text.clone()});
+      continue;
+    };
+    let old_node = &old.nodes[old_file_order as usize];
+    if old_node.type_is != new_node.type_is {
+      changes.push(NodeChange::Retyped{old_file_order, new_file_order, old_type: old_node.type_is.clone(), new_type: new_node.type_is.clone()});
+    }
+    if old_node.text != new_node.text {
+      changes.push(NodeChange::TextEdited{old_file_order, new_file_order, old_text: old_node.text.clone(), new_text: new_node.text.clone()});
+    }
+
+    // Compare old links translated into new file orders
+    let old_links = links(old_node, &|idx| old_to_new.get(idx as usize).copied().flatten());
+    let new_links = links(new_node, &|idx| Some(idx));
+    let is_single_parent = |node: &Node| node.parent_idxs.len() <= 1 && node.parent_idxs_diff_type.is_empty();
+    if is_single_parent(old_node) && is_single_parent(new_node) {
+      if old_links != new_links {
+        changes.push(NodeChange::Moved{
+          old_file_order, new_file_order,
+          old_parent: old_node.parent_idxs.first().copied(),
+          new_parent: new_node.parent_idxs.first().copied(),
+        });
+      }
+      continue;
+    }
+    let added: Vec<LinkRef> = new_links.iter().flatten().filter(|link| !old_links.contains(&Some(**link))).copied().collect();
+    let removed: Vec<LinkRef> = old_links.iter().zip(old_node.parent_idxs.iter().chain(old_node.parent_idxs_diff_type.iter()))
+      .filter(|(mapped, _)| mapped.is_none_or(|link| !new_links.contains(&Some(link))))
+      .map(|(mapped, old_idx)| LinkRef{file_order: *old_idx, diff_type: mapped.map_or(old_node.parent_idxs_diff_type.contains(old_idx), |link| link.diff_type)})
+      .collect();
+    if !added.is_empty() || !removed.is_empty() {
+      changes.push(NodeChange::LinksChanged{old_file_order, new_file_order, added, removed});
+    }
+  }
+  for (old_idx, old_node) in old.nodes.iter().enumerate() {
+    if old_to_new[old_idx].is_none() {
+      changes.push(NodeChange::Removed{old_file_order: old_idx as u32, text: old_node.text.clone()});
+    }
+  }
+  return changes;
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::file_parse::{DATA_DIR, parse_file, parse_str};
+
+  const BASE: &str = "# Test (md-decision-trees)
+
+D: Which database?
+* O: Postgres
+  * P: Mature
+  * C: Needs ops time
+* O: SQLite
+  * P,4-C,1: No server to run";
+
+  #[test]
+  fn test_identical() {
+    let nodes = parse_file(DATA_DIR.join("05_comparative_encoding_output.md")).unwrap();
+    assert!(diff(&nodes, &nodes).is_empty());
+  }
+
+  #[test]
+  fn test_renumbered_links_are_not_changes() {
+    // Adding a node above shifts every comparative index, but the links still point at the same Options
+    let old = parse_str(BASE).unwrap();
+    let new = parse_str(&BASE.replace("D: Which database?", "D: Which database?\n* N: Decide by Friday")
+      .replace("P,4-C,1", "P,5-C,2")).unwrap();
+    assert_eq!(diff(&old, &new), vec![NodeChange::Added{new_file_order: 1, text: "Decide by Friday".to_string()}]);
+  }
+
+  #[test]
+  fn test_changes() {
+    let old = parse_str(BASE).unwrap();
+    let new = parse_str("# Test (md-decision-trees)
+
+D: Which database?
+* O: Postgres
+  * C: Needs a lot of ops time
+  * C: Mature
+* O: SQLite
+  * P,4: No server to run
+* O: MySQL").unwrap();
+    let changes = diff(&old, &new);
+    assert!(changes.contains(&NodeChange::Retyped{old_file_order: 2, new_file_order: 3, old_type: Some(NodeType::Pro), new_type: Some(NodeType::Con)}));
+    assert!(changes.contains(&NodeChange::TextEdited{
+      old_file_order: 3, new_file_order: 2, old_text: "Needs ops time".to_string(), new_text: "Needs a lot of ops time".to_string()}));
+    assert!(changes.contains(&NodeChange::LinksChanged{
+      old_file_order: 5, new_file_order: 5, added: vec![], removed: vec![LinkRef{file_order: 1, diff_type: true}]}));
+    assert!(changes.contains(&NodeChange::Added{new_file_order: 6, text: "MySQL".to_string()}));
+    assert_eq!(changes.len(), 4);
+  }
+
+  #[test]
+  fn test_moved_and_removed() {
+    let old = parse_str(BASE).unwrap();
+    let new = parse_str("# Test (md-decision-trees)
+
+D: Which database?
+* O: Postgres
+* O: SQLite
+  * P: Mature").unwrap();
+    let changes = diff(&old, &new);
+    assert!(changes.contains(&NodeChange::Moved{old_file_order: 2, new_file_order: 3, old_parent: Some(1), new_parent: Some(2)}));
+    assert!(changes.contains(&NodeChange::Removed{old_file_order: 3, text: "Needs ops time".to_string()}));
+    assert!(changes.contains(&NodeChange::Removed{old_file_order: 5, text: "No server to run".to_string()}));
+    assert_eq!(changes.len(), 3);
+  }
+
+  #[test]
+  fn test_text_similarity() {
+    assert_eq!(text_similarity("abc", "abc"), 1.0);
+    assert_eq!(text_similarity("", ""), 1.0);
+    assert!(text_similarity("Needs ops time", "needs OPS time!") > 0.9);
+    assert!(text_similarity("Mature", "No server to run") < MIN_TEXT_SIMILARITY);
+  }
+}
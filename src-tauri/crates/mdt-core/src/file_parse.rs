@@ -0,0 +1,76 @@
+use crate::config::Config;
+use crate::parsers::bullet_file_parser::BulletFileParser;
+use crate::structs::Nodes;
+use std::path::PathBuf;
+use std::error::Error;
+use std::fs::read_to_string;
+
+pub const REQUIRED_HEADER: &str = "(md-decision-trees)";
+
+// For non-const statics - test fixtures are only in debug builds, so release builds can't be pointed at them
+#[cfg(any(test, debug_assertions))]
+lazy_static::lazy_static! {
+  pub static ref DATA_DIR: PathBuf = PathBuf::from(format!("{top_dir}/test/data", top_dir=env!("CARGO_MANIFEST_DIR")));
+}
+
+/// Top level function called from the front end - in the format of the file's `.mdt.toml`
+pub fn parse_file(file_path: PathBuf) -> Result<Nodes, Box<dyn Error>> {
+  let config = Config::discover(&file_path)?;
+  return parse_str_with(&read_to_string(file_path)?, &config);
+}
+
+/// Parse a decision file's already read content - in the built in format
+pub fn parse_str(content: &str) -> Result<Nodes, Box<dyn Error>> {
+  return parse_str_with(content, &Config::default());
+}
+
+/// Parse content in a project's format - ex. its indent width & type tokens
+pub fn parse_str_with(content: &str, config: &Config) -> Result<Nodes, Box<dyn Error>> {
+  // Stray carriage returns are line ending debris - kept, each save would drop one more of them
+  let mut lines = content.lines().map(|line| line.trim_end_matches('\r'));
+
+  let first_line = lines.next().ok_or("File did not contain a first header line")?;
+  if !first_line.contains(REQUIRED_HEADER) { Err(format!("First line did not contain {}", REQUIRED_HEADER))?  }
+
+  let mut nodes = Nodes{title: first_line.to_string(), ..Default::default()};
+
+  let mut parser = BulletFileParser::with_config(config);
+  for line in lines {
+    match parser.handle_line(line) {
+      Err(e) => return Err(e.into()),
+      Ok(opt_node) => { if let Some(node) = opt_node { nodes.nodes.push(node); } }
+    };
+  }
+  return Ok(nodes);
+}
+
+/// Stable (FNV-1a) hash of a file's content - used to notice when a file changed outside of the app
+pub fn content_hash(content: &str) -> String {
+  let mut hash: u64 = 0xcbf29ce484222325;
+  for byte in content.bytes() {
+    hash ^= u64::from(byte);
+    hash = hash.wrapping_mul(0x100000001b3);
+  }
+  return format!("{:016x}", hash);
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_invalid_file() {
+    let node_res = parse_file(DATA_DIR.join("00_invalid_file.md"));
+    assert!(node_res.is_err());
+    if let Err(err) = node_res {
+      assert!(err.to_string().contains("md-decision-trees"));
+    }
+  }
+
+  #[test]
+  fn test_content_hash() {
+    assert_eq!(content_hash(""), "cbf29ce484222325");
+    assert_eq!(content_hash("a"), "af63dc4c8601ec8c");
+    assert_ne!(content_hash("D: X\n"), content_hash("D: X\r\n"));
+  }
+}
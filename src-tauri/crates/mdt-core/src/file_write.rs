@@ -0,0 +1,430 @@
+use crate::structs::{Nodes, Node, NodeType};
+use crate::file_parse::REQUIRED_HEADER;
+use crate::config::Config;
+use serde::{Serialize, Deserialize};
+use std::path::{Path, PathBuf};
+use std::error::Error;
+use std::fs::{copy, metadata, read_to_string, remove_file, rename, set_permissions, File};
+use std::io::{ErrorKind, Write};
+use std::convert::TryFrom;
+
+/// Convert a NodeType to its built in token - see `Config::token` for a project's own. Custom types have none
+#[allow(clippy::result_unit_err)]
+pub fn to_node_start_string(type_is: &NodeType) -> Result<String, ()> {
+  match type_is {
+      NodeType::Decision => Ok(String::from("D")), 
+      NodeType::Option => Ok(String::from("O")), 
+      NodeType::Pro => Ok(String::from("P")), 
+      NodeType::Con => Ok(String::from("C")), 
+      NodeType::Note => Ok(String::from("N")),
+      NodeType::Assumption => Ok(String::from("A")),
+      NodeType::Risk => Ok(String::from("R")),
+      NodeType::Chance => Ok(String::from("X")),
+      NodeType::Outcome => Ok(String::from("Y")),
+      NodeType::Custom(_) => Err(()),
+  }
+}
+
+/// `implicit_parent` is the parent the parser will give the node from its indent - a Pro / Con only needs its indexes
+/// written when they are something else
+fn add_opt_node_type(prefix: &mut String, node: &Node, implicit_parent: Option<u32>, config: &Config) -> Result<(), Box<dyn Error>> {
+  let found_type = match &node.type_is {
+    Some(t) => t,
+    None => return Ok(()),
+  };
+  prefix.push_str(config.token(found_type));
+
+  let has_diff_type_parents: bool = 
+    (*found_type == NodeType::Pro || *found_type == NodeType::Con) && !node.parent_idxs_diff_type.is_empty();
+ 
+  // Note - if this is empty, may implicitly have a single parent of the node above
+  if node.parent_idxs.is_empty() {
+    // parent_idxs must be present with parent_idxs_diff_type - ex. nodes built by hand through the wasm / python bindings
+    if !node.parent_idxs_diff_type.is_empty() { Err(format!("'{}' has links of the other type but no parent of its own type", node.text))? }
+  } else if node.parent_idxs.len() > 1 || has_diff_type_parents || is_explicit_parent(found_type, node, implicit_parent) {
+    prefix.push(',');
+    for (i, idx) in node.parent_idxs.iter().enumerate() {
+      if i > 0 {
+        prefix.push(',');
+      }
+      prefix.push_str(&idx.to_string());
+    }
+  }
+
+  if has_diff_type_parents {
+    prefix.push('-');
+    prefix.push_str(config.token(if *found_type == NodeType::Pro { &NodeType::Con } else { &NodeType::Pro }));
+    prefix.push(',');
+    for (i, idx) in node.parent_idxs_diff_type.iter().enumerate() {
+      if i > 0 {
+        prefix.push(',');
+      }
+      prefix.push_str(&idx.to_string());
+    }
+  }
+  
+  // Add the colon and space after the type and any indexes
+  prefix.push_str(": ");
+  return Ok(());
+}
+  
+/// A Pro / Con's single parent that isn't the node it's indented under
+pub(crate) fn is_explicit_parent(found_type: &NodeType, node: &Node, implicit_parent: Option<u32>) -> bool {
+  return (*found_type == NodeType::Pro || *found_type == NodeType::Con) && node.parent_idxs.first().copied() != implicit_parent;
+}
+
+/// Parent each node gets from its indent when parsed - the nearest line above at a lower level, same as the parser
+#[derive(Default)]
+pub struct ImplicitParents {
+  stack: Vec<(u32, u32)>, //< (level, idx) of lines that may still be a parent
+  num_nodes: u32,
+}
+
+impl ImplicitParents {
+  /// Implicit parent of the next node in file order
+  pub fn next(&mut self, node: &Node) -> Option<u32> {
+    while self.stack.last().is_some_and(|(level, _)| *level >= node.level) { self.stack.pop(); }
+    let parent = self.stack.last().map(|(_, idx)| *idx);
+    self.stack.push((node.level, self.num_nodes));
+    self.num_nodes += 1;
+    return parent;
+  }
+}
+
+/// Options for how a file is saved - the write itself is always atomic
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[serde(default)]
+pub struct SaveOptions {
+  pub num_backups: u32, //< Rotating `<file>.<n>.bak` copies of the previous content to keep - 0 for none
+}
+
+/// A single node's line in the built in format - without the blank line written before top level nodes. Assumes a
+/// Pro / Con with a single parent is indented under it - use `node_to_line_under` when writing a whole file
+pub fn node_to_line(node: &Node) -> Result<String, Box<dyn Error>> {
+  return node_to_line_under(node, node.parent_idxs.first().copied(), &Config::default());
+}
+
+/// A single node's line, given the parent it will get from its indent - see `ImplicitParents`
+pub fn node_to_line_under(node: &Node, implicit_parent: Option<u32>, config: &Config) -> Result<String, Box<dyn Error>> {
+  if let Some(NodeType::Custom(id)) = &node.type_is {
+    if !config.custom_types.contains_key(id) { Err(format!("'{}' is a {} node, which the .mdt.toml doesn't declare", node.text, id))? }
+  }
+  let mut prefix: String = String::new();
+  if node.level == 0 {
+    add_opt_node_type(&mut prefix, node, implicit_parent, config)?;
+  } else {
+    let num_spaces = config.indent_width.checked_mul(node.level-1).ok_or(format!("Level {} is too deep", node.level))?;
+    prefix = " ".repeat(usize::try_from(num_spaces)?);
+    prefix.push_str("* ");
+    add_opt_node_type(&mut prefix, node, implicit_parent, config)?;
+  }
+  prefix.push_str(&node.text);
+  return Ok(prefix);
+}
+
+/// Serialize nodes to the file's content in the built in format - the file path is only used for a title if the nodes
+/// have none
+pub fn nodes_to_string(nodes: &Nodes, file_path: &Path) -> Result<String, Box<dyn Error>> {
+  return nodes_to_string_with(nodes, file_path, &Config::default());
+}
+
+/// Serialize nodes in a project's format - ex. its indent width & type tokens
+pub fn nodes_to_string_with(nodes: &Nodes, file_path: &Path, config: &Config) -> Result<String, Box<dyn Error>> {
+  let mut content = if !nodes.title.is_empty() {
+    nodes.title.clone()
+  } else {
+    let file_stem = file_path.file_stem().and_then(|stem| stem.to_str()).ok_or("File path has no name to use as a title")?;
+    format!("# {} {}", file_stem, REQUIRED_HEADER)
+  };
+  let mut implicit_parents = ImplicitParents::default();
+  for node in &nodes.nodes {
+    content.push('\n');
+    if node.level == 0 {
+      content.push('\n');
+    }
+    content.push_str(&node_to_line_under(node, implicit_parents.next(node), config)?);
+  }
+  return Ok(content);
+}
+
+pub fn write_nodes_to_file(nodes: Nodes, file_path: PathBuf) -> Result<(), Box<dyn Error>> {
+  return write_nodes_to_file_with(nodes, file_path, &SaveOptions::default());
+}
+
+/// Everything is serialized before the file is touched, then written to a temp file that replaces the original
+/// - so a failure part way through never leaves the user with a truncated file
+pub fn write_nodes_to_file_with(nodes: Nodes, file_path: PathBuf, options: &SaveOptions) -> Result<(), Box<dyn Error>> {
+  let content = nodes_to_file_content(&nodes, &file_path)?;
+  return write_file_atomic(&file_path, &content, options);
+}
+
+/// Exact content a save would write - in the format of the file's `.mdt.toml`, keeping an existing file's line endings
+/// & trailing newline
+pub fn nodes_to_file_content(nodes: &Nodes, file_path: &Path) -> Result<String, Box<dyn Error>> {
+  let config = Config::discover(file_path)?;
+  return match_line_endings(nodes_to_string_with(nodes, file_path, &config)?, file_path);
+}
+
+/// Content with the line endings & trailing newline of the file it will replace - as is if there is no file yet
+pub fn match_line_endings(mut content: String, file_path: &Path) -> Result<String, Box<dyn Error>> {
+  let existing_content = match read_to_string(file_path) {
+    Ok(existing_content) => existing_content,
+    Err(err) if err.kind() == ErrorKind::NotFound => return Ok(content),
+    Err(err) => return Err(err.into()),
+  };
+  if existing_content.ends_with('\n') { content.push('\n'); }
+  if existing_content.contains("\r\n") { content = content.replace('\n', "\r\n"); }
+  return Ok(content);
+}
+
+/// Write to a temp file next to the original, fsync it, then rename it over the original - keeping its permissions
+pub fn write_file_atomic(file_path: &Path, content: &str, options: &SaveOptions) -> Result<(), Box<dyn Error>> {
+  let existing_permissions = match metadata(file_path) {
+    Ok(file_metadata) => Some(file_metadata.permissions()),
+    Err(err) if err.kind() == ErrorKind::NotFound => None,
+    Err(err) => return Err(err.into()),
+  };
+  if existing_permissions.is_some() && options.num_backups > 0 {
+    rotate_backups(file_path, options.num_backups)?;
+  }
+
+  let file_name = file_path.file_name().ok_or("File path has no file name")?.to_string_lossy().to_string();
+  let tmp_path = file_path.with_file_name(format!(".{}.mdt-tmp", file_name));
+  let write_tmp = || -> Result<(), Box<dyn Error>> {
+    let mut tmp_file = File::create(&tmp_path)?;
+    tmp_file.write_all(content.as_bytes())?;
+    tmp_file.sync_all()?;
+    if let Some(permissions) = &existing_permissions {
+      set_permissions(&tmp_path, permissions.clone())?;
+    }
+    rename(&tmp_path, file_path)?;
+    return Ok(());
+  };
+  if let Err(err) = write_tmp() {
+    let _ = remove_file(&tmp_path); //< Best effort - the original error is what matters
+    return Err(err);
+  }
+  sync_parent_dir(file_path);
+  return Ok(());
+}
+
+/// Path of the nth most recent backup - 1 is the newest
+pub fn backup_path(file_path: &Path, n: u32) -> PathBuf {
+  let file_name = file_path.file_name().map_or(String::new(), |name| name.to_string_lossy().to_string());
+  return file_path.with_file_name(format!("{}.{}.bak", file_name, n));
+}
+
+/// Shift each backup back one, dropping the oldest, then copy the current file in as the newest
+fn rotate_backups(file_path: &Path, num_backups: u32) -> Result<(), Box<dyn Error>> {
+  for n in (1..num_backups).rev() {
+    let older = backup_path(file_path, n);
+    if older.exists() { rename(&older, backup_path(file_path, n + 1))?; }
+  }
+  copy(file_path, backup_path(file_path, 1))?;
+  return Ok(());
+}
+
+/// Make the rename itself durable - not supported on every platform, so failures are ignored
+fn sync_parent_dir(file_path: &Path) {
+  #[cfg(unix)]
+  if let Some(dir) = file_path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+    if let Ok(dir_file) = File::open(dir) { let _ = dir_file.sync_all(); }
+  }
+  #[cfg(not(unix))]
+  let _ = file_path;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use crate::file_parse::{DATA_DIR, parse_str};
+    use crate::test_utils::scratch_dir;
+
+    fn decision(text: &str) -> Nodes {
+        let decision = Node::new(text.to_string(), NodeType::Decision, 0, 0, vec![], vec![]);
+        return Nodes {title: "# Test (md-decision-trees)".to_string(), nodes: vec![decision]};
+    }
+
+    #[test]
+    fn test_preserves_line_endings() {
+        let path = scratch_dir("file_write", "line_endings").join("crlf.md");
+        let original = "# Test (md-decision-trees)\r\n\r\nD: X\r\n* O: Y\r\n";
+        fs::write(&path, original).unwrap();
+        write_nodes_to_file(parse_str(original).unwrap(), path.clone()).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), original);
+    }
+
+    #[test]
+    fn test_rotating_backups() {
+        let path = scratch_dir("file_write", "backups").join("backups.md");
+        let options = SaveOptions {num_backups: 2};
+        for text in ["First", "Second", "Third", "Fourth"] {
+            write_nodes_to_file_with(decision(text), path.clone(), &options).unwrap();
+        }
+        assert!(fs::read_to_string(&path).unwrap().contains("D: Fourth"));
+        assert!(fs::read_to_string(backup_path(&path, 1)).unwrap().contains("D: Third"));
+        assert!(fs::read_to_string(backup_path(&path, 2)).unwrap().contains("D: Second"));
+        assert!(!backup_path(&path, 3).exists());
+    }
+
+    #[test]
+    fn test_failed_write_keeps_original() {
+        let path = scratch_dir("file_write", "failed_write").join("failed.md");
+        write_nodes_to_file(decision("Original"), path.clone()).unwrap();
+        let mut nodes = decision("Replacement");
+        nodes.nodes.push(Node::new("Too deep".to_string(), NodeType::Option, 1, u32::MAX, vec![0], vec![]));
+        assert!(write_nodes_to_file(nodes, path.clone()).is_err());
+        assert!(fs::read_to_string(&path).unwrap().contains("D: Original"));
+        assert_eq!(fs::read_dir(path.parent().unwrap()).unwrap()
+            .filter(|entry| entry.as_ref().unwrap().file_name().to_string_lossy().ends_with(".mdt-tmp")).count(), 0);
+    }
+
+    #[test]
+    fn test_configured_format() {
+        let path = scratch_dir("file_write", "configured").join("db.md");
+        fs::write(path.with_file_name(".mdt.toml"), "indent_width = 4\n[node_types.con]\ntoken = \"Con\"").unwrap();
+        let content = "# Test (md-decision-trees)\n\nD: X\n* O: Y\n    * Con: Z\n* O: W\n    * Con,1: Z for Y";
+        fs::write(&path, content).unwrap();
+        let nodes = crate::file_parse::parse_file(path.clone()).unwrap();
+        assert_eq!(nodes.nodes[2].type_is, Some(NodeType::Con));
+        assert_eq!(nodes.nodes[4].parent_idxs, vec![1]);
+        assert_eq!(nodes_to_file_content(&nodes, &path).unwrap(), content);
+    }
+
+    #[test]
+    fn test_custom_types() {
+        let config = Config::from_toml("[custom_types.question]\ntoken = \"Q\"").unwrap();
+        let content = "# Test (md-decision-trees)\n\nD: X\n* O: Y\n  * Q: Z";
+        let nodes = crate::file_parse::parse_str_with(content, &config).unwrap();
+        assert_eq!(nodes_to_string_with(&nodes, Path::new("x.md"), &config).unwrap(), content);
+        assert!(nodes_to_string(&nodes, Path::new("x.md")).is_err()); //< Undeclared without the config
+    }
+
+    #[test]
+    fn test_diff_type_links_without_parent() {
+        let mut nodes = decision("X");
+        nodes.nodes.push(Node::new("Y".to_string(), NodeType::Option, 1, 1, vec![0], vec![]));
+        nodes.nodes.push(Node::new("Z".to_string(), NodeType::Pro, 2, 2, vec![], vec![1]));
+        assert!(nodes_to_string(&nodes, Path::new("x.md")).unwrap_err().to_string().contains("'Z'"));
+        assert!(node_to_line(&nodes.nodes[2]).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_preserves_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+        let path = scratch_dir("file_write", "permissions").join("permissions.md");
+        write_nodes_to_file(decision("First"), path.clone()).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+        write_nodes_to_file(decision("Second"), path.clone()).unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+    }
+
+    #[test]
+    fn test_comparative_encoding_writing() {
+        // Create a test file path
+        let output_file_path = DATA_DIR.join("05_comparative_encoding_output.md");
+        
+        // Create test nodes with various comparative configurations
+        let mut nodes = Vec::new();
+        
+        nodes.push(Node::new(
+            "Decision Node".to_string(),
+            NodeType::Decision,
+            0,
+            0,
+            vec![],
+            vec![],
+        ));
+        let opt_1_idx = 1;
+        nodes.push(Node::new(
+            "Option 1".to_string(),
+            NodeType::Option,
+            opt_1_idx,
+            1,
+            vec![],
+            vec![],
+        ));
+        // Test case for Pro node implicitly linking to Option 1
+        nodes.push(Node::new(
+            "Pro for Option 1".to_string(),
+            NodeType::Pro,
+            2,
+            2,
+            vec![],
+            vec![],
+        ));
+        let opt_2_idx = 4;
+        // Test case for being Pro for multiple, but not a Con
+        nodes.push(Node::new(
+            "Pro for Options 1 and 2".to_string(),
+            NodeType::Pro,
+            3,
+            2,
+            vec![opt_1_idx, opt_2_idx],
+            vec![],
+        ));
+        nodes.push(Node::new(
+            "Option 2".to_string(),
+            NodeType::Option,
+            opt_2_idx,
+            1,
+            vec![],
+            vec![],
+        ));
+        // Test case for Con node implicitly linking to Option 2
+        nodes.push(Node::new(
+            "Con for Option 2".to_string(),
+            NodeType::Con,
+            5,
+            2,
+            vec![],
+            vec![],
+        ));
+        let opt_3_idx = 7;
+        // Test case for Pro node that is also a Con for 2 options
+        nodes.push(Node::new(
+            "Pro for Option 2, Con for Options 1 and 3".to_string(),
+            NodeType::Pro,
+            6,
+            2,
+            vec![opt_2_idx],
+            vec![opt_1_idx, opt_3_idx],
+        ));
+        nodes.push(Node::new(
+            "Option 3".to_string(),
+            NodeType::Option,
+            opt_3_idx,
+            1,
+            vec![],
+            vec![],
+        ));
+        // Test case for Pro node linking to 2 options
+        nodes.push(Node::new(
+            "Con for Option 3, but Pro for 1 and 2".to_string(),
+            NodeType::Con,
+            8,
+            2,
+            vec![opt_3_idx],
+            vec![opt_1_idx, opt_2_idx],
+        ));
+        // Write the test file and read it back
+        let nodes = Nodes {title: String::new(), nodes};
+        let write_res = write_nodes_to_file(nodes, output_file_path.clone());
+        assert!(write_res.is_ok());
+        let output_content = fs::read_to_string(output_file_path.clone()).expect("Failed to read output file");
+        
+        // Check for the expected comparative encoding format
+        assert!(output_content.contains("D: Decision Node"));
+        assert!(output_content.contains("* O: Option 1"));
+        assert!(output_content.contains("  * P: Pro for Option 1"));
+        assert!(output_content.contains("  * P,1,4: Pro for Options 1 and 2"));
+        assert!(output_content.contains("* O: Option 2"));
+        assert!(output_content.contains("  * C: Con for Option 2"));
+        assert!(output_content.contains("  * P,4-C,1,7: Pro for Option 2, Con for Options 1 and 3"));
+        assert!(output_content.contains("* O: Option 3"));
+        assert!(output_content.contains("  * C,7-P,1,4: Con for Option 3, but Pro for 1 and 2"));
+    }
+}
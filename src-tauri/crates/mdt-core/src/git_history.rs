@@ -0,0 +1,117 @@
+use crate::config::Config;
+use crate::file_parse::parse_str_with;
+use crate::structs::Nodes;
+use serde::{Serialize, Deserialize};
+
+use git2::{Oid, Repository, Sort, Tree};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+/// A commit that changed a decision file - newest first from file_history
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct FileCommit {
+  pub id: String, //< Full hash - pass back as the revision to load the file at this commit
+  pub summary: String,
+  pub author: String,
+  pub time: u32, //< Unix seconds - u64 isn't allowed in bindings
+}
+
+/// The repository containing a file & the file's path relative to its working directory
+fn open_repo(file_path: &Path) -> Result<(Repository, PathBuf), Box<dyn Error>> {
+  let file_path = file_path.canonicalize()?;
+  let repo = Repository::discover(file_path.parent().ok_or("File has no parent directory")?)?;
+  let workdir = repo.workdir().ok_or("Repository has no working directory")?.canonicalize()?;
+  let relative_path = file_path.strip_prefix(&workdir)?.to_path_buf();
+  return Ok((repo, relative_path));
+}
+
+fn blob_id(tree: &Tree, relative_path: &Path) -> Option<Oid> {
+  return tree.get_path(relative_path).ok().map(|entry| entry.id());
+}
+
+/// Commits reachable from HEAD where the file's content differs from the first parent's
+pub fn file_history(file_path: &Path) -> Result<Vec<FileCommit>, Box<dyn Error>> {
+  let (repo, relative_path) = open_repo(file_path)?;
+  let mut revwalk = repo.revwalk()?;
+  revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+  revwalk.push_head()?;
+  let mut commits = Vec::new();
+  for oid in revwalk {
+    let commit = repo.find_commit(oid?)?;
+    let file_id = blob_id(&commit.tree()?, &relative_path);
+    let parent_file_id = match commit.parent(0) {
+      Ok(parent) => blob_id(&parent.tree()?, &relative_path),
+      Err(_) => None, //< Root commit
+    };
+    if file_id.is_none() || file_id == parent_file_id { continue; }
+    commits.push(FileCommit{
+      id: commit.id().to_string(),
+      summary: commit.summary().unwrap_or_default().to_string(),
+      author: commit.author().name().unwrap_or_default().to_string(),
+      time: u32::try_from(commit.time().seconds()).unwrap_or(0),
+    });
+  }
+  return Ok(commits);
+}
+
+/// The file as of a revision - anything git understands, ex. a commit hash, `HEAD~2` or a branch name. Parsed with
+/// today's `.mdt.toml`
+pub fn nodes_at_revision(file_path: &Path, revision: &str) -> Result<Nodes, Box<dyn Error>> {
+  let config = Config::discover(file_path)?;
+  let (repo, relative_path) = open_repo(file_path)?;
+  let tree = repo.revparse_single(revision)?.peel_to_commit()?.tree()?;
+  let entry = tree.get_path(&relative_path)
+    .map_err(|_| format!("{} doesn't exist at {}", relative_path.display(), revision))?;
+  let blob = repo.find_blob(entry.id())?;
+  return parse_str_with(std::str::from_utf8(blob.content())?, &config);
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use git2::Signature;
+  use crate::test_utils::scratch_dir;
+  use std::fs;
+
+  /// Repo with one commit per version of decision.md - & an unrelated commit in between
+  fn repo_with_history(name: &str, versions: &[&str]) -> PathBuf {
+    let dir = scratch_dir("git_history", name);
+    let repo = Repository::init(&dir).unwrap();
+    let signature = Signature::new("Tester", "tester@example.com", &git2::Time::new(1_700_000_000, 0)).unwrap();
+    let commit_files = |files: &[(&str, &str)], message: &str| {
+      let mut index = repo.index().unwrap();
+      for (file_name, content) in files {
+        fs::write(dir.join(file_name), content).unwrap();
+        index.add_path(Path::new(file_name)).unwrap();
+      }
+      index.write().unwrap();
+      let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+      let parent = repo.head().ok().map(|head| head.peel_to_commit().unwrap());
+      repo.commit(Some("HEAD"), &signature, &signature, message, &tree, &parent.iter().collect::<Vec<_>>()).unwrap();
+    };
+    for (version_idx, content) in versions.iter().enumerate() {
+      commit_files(&[("decision.md", content)], &format!("Version {}", version_idx + 1));
+      commit_files(&[("other.md", &format!("Unrelated {}", version_idx))], "Unrelated change");
+    }
+    return dir.join("decision.md");
+  }
+
+  #[test]
+  fn test_file_history() {
+    let file_path = repo_with_history("history", &[
+      "# Test (md-decision-trees)\n\nD: Which?",
+      "# Test (md-decision-trees)\n\nD: Which?\n* O: This one",
+    ]);
+    let history = file_history(&file_path).unwrap();
+    let summaries: Vec<&str> = history.iter().map(|commit| commit.summary.as_str()).collect();
+    assert_eq!(summaries, vec!["Version 2", "Version 1"]);
+    assert_eq!(history[0].author, "Tester");
+
+    let old = nodes_at_revision(&file_path, &history[1].id).unwrap();
+    let new = nodes_at_revision(&file_path, "HEAD").unwrap();
+    assert_eq!(old.nodes.len(), 1);
+    assert_eq!(new.nodes[1].text, "This one");
+    assert_eq!(nodes_at_revision(&file_path, "HEAD~1").unwrap(), new); //< The unrelated commit didn't change it
+  }
+}
@@ -0,0 +1,83 @@
+use crate::structs::{Nodes, NodeType};
+
+/// How a child is tied to its parent
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Link {
+  Same, //< From parent_idxs - child is its own type for this parent
+  DiffType, //< From parent_idxs_diff_type - a Pro is a Con for this parent & vice versa
+}
+
+/// Parent -> children edges built from each node's parent_idxs / parent_idxs_diff_type
+pub struct Graph<'a> {
+  pub nodes: &'a Nodes,
+  children: Vec<Vec<(u32, Link)>>,
+}
+
+impl<'a> Graph<'a> {
+  pub fn new(nodes: &'a Nodes) -> Self {
+    let mut children = vec![Vec::new(); nodes.nodes.len()];
+    for node in &nodes.nodes {
+      let same = node.parent_idxs.iter().map(|idx| (*idx, Link::Same));
+      let diff = node.parent_idxs_diff_type.iter().map(|idx| (*idx, Link::DiffType));
+      for (parent_idx, link) in same.chain(diff) {
+        // Out of range indexes are reported by validation - just skip them here
+        if let Some(parent_children) = children.get_mut(parent_idx as usize) {
+          parent_children.push((node.file_order, link));
+        }
+      }
+    }
+    return Graph{nodes, children};
+  }
+
+  pub fn children(&self, idx: u32) -> &[(u32, Link)] {
+    return self.children.get(idx as usize).map_or(&[], |children| children.as_slice());
+  }
+
+  /// Type a child acts as for this parent - a comparative Pro is a Con through a DiffType link
+  pub fn linked_type(&self, child_idx: u32, link: Link) -> Option<NodeType> {
+    let type_is = self.nodes.nodes.get(child_idx as usize)?.type_is.clone()?;
+    return Some(match (type_is, link) {
+      (NodeType::Pro, Link::DiffType) => NodeType::Con,
+      (NodeType::Con, Link::DiffType) => NodeType::Pro,
+      (type_is, _) => type_is,
+    });
+  }
+
+  /// Children acting as the given type for this parent
+  pub fn children_of_type(&self, idx: u32, node_type: NodeType) -> Vec<u32> {
+    return self.children(idx).iter()
+      .filter(|(child_idx, link)| self.linked_type(*child_idx, *link).as_ref() == Some(&node_type))
+      .map(|(child_idx, _)| *child_idx)
+      .collect();
+  }
+
+  /// Options a node is linked to - either way
+  pub fn linked_options(&self, idx: u32) -> Vec<u32> {
+    let Some(node) = self.nodes.nodes.get(idx as usize) else { return Vec::new(); };
+    let mut options: Vec<u32> = node.parent_idxs.iter().chain(node.parent_idxs_diff_type.iter())
+      .filter(|parent_idx| self.nodes.nodes.get(**parent_idx as usize).is_some_and(|parent| parent.type_is == Some(NodeType::Option)))
+      .copied()
+      .collect();
+    options.sort_unstable();
+    options.dedup();
+    return options;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::file_parse::{DATA_DIR, parse_file};
+
+  #[test]
+  fn test_comparative_children() {
+    let nodes = parse_file(DATA_DIR.join("05_comparative_encoding_output.md")).unwrap();
+    let graph = Graph::new(&nodes);
+    // Option 1 - its own Pro, the shared Pro, & the Con for 3 that is a Pro for 1 & 2
+    assert_eq!(graph.children_of_type(1, NodeType::Pro), vec![2, 3, 8]);
+    // The Pro for Option 2 is a Con for Option 1
+    assert_eq!(graph.children_of_type(1, NodeType::Con), vec![6]);
+    assert_eq!(graph.linked_options(6), vec![1, 4, 7]);
+    assert_eq!(graph.linked_options(2), vec![1]);
+  }
+}
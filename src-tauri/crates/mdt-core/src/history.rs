@@ -0,0 +1,327 @@
+use crate::structs::{Node, Nodes, NodeType};
+use serde::{Serialize, Deserialize};
+use std::collections::VecDeque;
+
+/// Max operations kept to undo per file
+pub const DEFAULT_HISTORY_LIMIT: usize = 100;
+
+/// A single change to the tree model - file_orders are always the node's index at the time the op is applied
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub enum Op {
+  EditText { file_order: u32, text: String },
+  /// Inserted at node.file_order - later nodes & any indexes pointing at them shift down one. Not above lines indented
+  /// deeper than it, which would become its children
+  InsertNode { node: Node },
+  /// Removes the node with the lines indented under it - later nodes & indexes shift up. Links to removed nodes are
+  /// dropped. A comparative Pro / Con left with only links of the other type becomes that type, ex. `P,4-C,1,7` without
+  /// node 4 is `C,1,7`
+  DeleteNode { file_order: u32 },
+  /// Replace the comparative links (or the implicit parent) of a node
+  SetLinks { file_order: u32, parent_idxs: Vec<u32>, parent_idxs_diff_type: Vec<u32> },
+  SetType { file_order: u32, type_is: Option<NodeType> },
+  /// Applied in order, undone as one - ex. inserting a node with its children
+  Batch { ops: Vec<Op> },
+}
+
+impl Op {
+  /// Short description for showing the history to the user
+  pub fn describe(&self) -> String {
+    match self {
+      Op::EditText{file_order, ..} => format!("Edit text of node {}", file_order),
+      Op::InsertNode{node} => format!("Insert '{}'", node.text),
+      Op::DeleteNode{file_order} => format!("Delete node {}", file_order),
+      Op::SetLinks{file_order, ..} => format!("Change links of node {}", file_order),
+      Op::SetType{file_order, ..} => format!("Change type of node {}", file_order),
+      Op::Batch{ops} => format!("{} changes", ops.len()),
+    }
+  }
+}
+
+fn get_node_mut(nodes: &mut Nodes, file_order: u32) -> Result<&mut Node, String> {
+  return nodes.nodes.get_mut(file_order as usize).ok_or(format!("No node {} to change", file_order));
+}
+
+/// Removes a single node - returns the op that puts it & its links back
+fn delete_node(nodes: &mut Nodes, idx: u32) -> Op {
+  let removed = nodes.nodes.remove(idx as usize);
+  // Links to the removed node are restored after it is re-inserted - so record them with the original indexes
+  let mut restore_links = Vec::new();
+  for other in nodes.nodes.iter_mut() {
+    if other.parent_idxs.contains(&idx) || other.parent_idxs_diff_type.contains(&idx) {
+      restore_links.push(Op::SetLinks{
+        file_order: other.file_order,
+        parent_idxs: other.parent_idxs.clone(),
+        parent_idxs_diff_type: other.parent_idxs_diff_type.clone(),
+      });
+      other.parent_idxs.retain(|parent_idx| *parent_idx != idx);
+      other.parent_idxs_diff_type.retain(|parent_idx| *parent_idx != idx);
+      // Links of the other type can't be written without one of this type - so it becomes the other type
+      if other.parent_idxs.is_empty() && !other.parent_idxs_diff_type.is_empty() {
+        let flipped = match other.type_is {
+          Some(NodeType::Pro) => Some(NodeType::Con),
+          Some(NodeType::Con) => Some(NodeType::Pro),
+          _ => other.type_is.clone(),
+        };
+        restore_links.push(Op::SetType{file_order: other.file_order, type_is: std::mem::replace(&mut other.type_is, flipped)});
+        other.parent_idxs = std::mem::take(&mut other.parent_idxs_diff_type);
+      }
+    }
+    let shift = |parent_idx: &mut u32| if *parent_idx > idx { *parent_idx -= 1; };
+    shift(&mut other.file_order);
+    other.parent_idxs.iter_mut().for_each(shift);
+    other.parent_idxs_diff_type.iter_mut().for_each(shift);
+  }
+  let mut undo_ops = vec![Op::InsertNode{node: removed}];
+  undo_ops.extend(restore_links);
+  return if undo_ops.len() == 1 { undo_ops.remove(0) } else { Op::Batch{ops: undo_ops} };
+}
+
+/// Apply an op to the nodes & return the op that undoes it
+pub fn apply(nodes: &mut Nodes, op: &Op) -> Result<Op, String> {
+  match op {
+    Op::EditText{file_order, text} => {
+      let node = get_node_mut(nodes, *file_order)?;
+      let old_text = std::mem::replace(&mut node.text, text.clone());
+      return Ok(Op::EditText{file_order: *file_order, text: old_text});
+    },
+    Op::SetLinks{file_order, parent_idxs, parent_idxs_diff_type} => {
+      let node = get_node_mut(nodes, *file_order)?;
+      let old_parent_idxs = std::mem::replace(&mut node.parent_idxs, parent_idxs.clone());
+      let old_diff_type = std::mem::replace(&mut node.parent_idxs_diff_type, parent_idxs_diff_type.clone());
+      return Ok(Op::SetLinks{file_order: *file_order, parent_idxs: old_parent_idxs, parent_idxs_diff_type: old_diff_type});
+    },
+    Op::SetType{file_order, type_is} => {
+      let node = get_node_mut(nodes, *file_order)?;
+      let old_type = std::mem::replace(&mut node.type_is, type_is.clone());
+      return Ok(Op::SetType{file_order: *file_order, type_is: old_type});
+    },
+    Op::InsertNode{node} => {
+      let idx = node.file_order;
+      if idx as usize > nodes.nodes.len() { return Err(format!("Can't insert node at {} - only {} nodes", idx, nodes.nodes.len())); }
+      if nodes.nodes.get(idx as usize).is_some_and(|next| next.level > node.level) {
+        return Err(format!("Can't insert '{}' at {} - the lines indented below it would become its children", node.text, idx));
+      }
+      let shift = |parent_idx: &mut u32| if *parent_idx >= idx { *parent_idx += 1; };
+      for other in nodes.nodes.iter_mut() {
+        shift(&mut other.file_order);
+        other.parent_idxs.iter_mut().for_each(shift);
+        other.parent_idxs_diff_type.iter_mut().for_each(shift);
+      }
+      nodes.nodes.insert(idx as usize, node.clone());
+      return Ok(Op::DeleteNode{file_order: idx});
+    },
+    Op::DeleteNode{file_order} => {
+      let idx = *file_order;
+      let Some(node) = nodes.nodes.get(idx as usize) else { return Err(format!("No node {} to delete", idx)); };
+      // Lines left indented under a node above would be read back as its children - so they go too, deepest index first
+      let num_children = nodes.nodes[idx as usize + 1..].iter().take_while(|other| other.level > node.level).count() as u32;
+      let mut undo_ops: Vec<Op> = (idx..=idx + num_children).rev().map(|delete_idx| delete_node(nodes, delete_idx)).collect();
+      undo_ops.reverse();
+      return Ok(if undo_ops.len() == 1 { undo_ops.remove(0) } else { Op::Batch{ops: undo_ops} });
+    },
+    Op::Batch{ops} => {
+      let mut undo_ops = Vec::new();
+      for batch_op in ops {
+        match apply(nodes, batch_op) {
+          Ok(undo_op) => undo_ops.push(undo_op),
+          Err(err) => {
+            // Roll back what was already applied so a failed batch changes nothing
+            for undo_op in undo_ops.iter().rev() { let _ = apply(nodes, undo_op); }
+            return Err(err);
+          }
+        }
+      }
+      undo_ops.reverse();
+      return Ok(Op::Batch{ops: undo_ops});
+    },
+  }
+}
+
+/// Ops that were applied, with what undoes them
+#[derive(Clone, Debug)]
+struct Entry { op: Op, undo_op: Op }
+
+/// What can be undone / redone - most recent first
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct HistorySummary {
+  pub undo: Vec<String>,
+  pub redo: Vec<String>,
+}
+
+/// Bounded undo / redo stacks for one file
+#[derive(Clone, Debug)]
+pub struct History {
+  undo_stack: VecDeque<Entry>,
+  redo_stack: Vec<Entry>,
+  limit: usize,
+}
+
+impl Default for History {
+  fn default() -> Self { History::new(DEFAULT_HISTORY_LIMIT) }
+}
+
+impl History {
+  pub fn new(limit: usize) -> Self {
+    History{undo_stack: VecDeque::new(), redo_stack: Vec::new(), limit}
+  }
+
+  /// Applying something new clears anything that could have been redone
+  pub fn apply(&mut self, nodes: &mut Nodes, op: Op) -> Result<(), String> {
+    let undo_op = apply(nodes, &op)?;
+    self.redo_stack.clear();
+    self.undo_stack.push_back(Entry{op, undo_op});
+    if self.undo_stack.len() > self.limit { self.undo_stack.pop_front(); }
+    return Ok(());
+  }
+
+  /// False if there was nothing to undo
+  pub fn undo(&mut self, nodes: &mut Nodes) -> Result<bool, String> {
+    let Some(entry) = self.undo_stack.pop_back() else { return Ok(false); };
+    if let Err(err) = apply(nodes, &entry.undo_op) {
+      self.undo_stack.push_back(entry);
+      return Err(err);
+    }
+    self.redo_stack.push(entry);
+    return Ok(true);
+  }
+
+  /// False if there was nothing to redo
+  pub fn redo(&mut self, nodes: &mut Nodes) -> Result<bool, String> {
+    let Some(mut entry) = self.redo_stack.pop() else { return Ok(false); };
+    match apply(nodes, &entry.op) {
+      Ok(undo_op) => entry.undo_op = undo_op,
+      Err(err) => {
+        self.redo_stack.push(entry);
+        return Err(err);
+      }
+    }
+    self.undo_stack.push_back(entry);
+    return Ok(true);
+  }
+
+  pub fn summary(&self) -> HistorySummary {
+    return HistorySummary{
+      undo: self.undo_stack.iter().rev().map(|entry| entry.op.describe()).collect(),
+      redo: self.redo_stack.iter().rev().map(|entry| entry.op.describe()).collect(),
+    };
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::file_parse::{DATA_DIR, parse_file, parse_str};
+  use crate::file_write::nodes_to_string;
+  use std::path::Path;
+
+  fn comparative_nodes() -> Nodes {
+    return parse_file(DATA_DIR.join("05_comparative_encoding_output.md")).unwrap();
+  }
+
+  #[test]
+  fn test_undo_redo_text() {
+    let original = comparative_nodes();
+    let mut nodes = original.clone();
+    let mut history = History::default();
+    history.apply(&mut nodes, Op::EditText{file_order: 1, text: "Option One".to_string()}).unwrap();
+    assert_eq!(nodes.nodes[1].text, "Option One");
+    assert!(history.undo(&mut nodes).unwrap());
+    assert_eq!(nodes, original);
+    assert!(history.redo(&mut nodes).unwrap());
+    assert_eq!(nodes.nodes[1].text, "Option One");
+    assert!(!history.redo(&mut nodes).unwrap());
+  }
+
+  #[test]
+  fn test_delete_restores_links() {
+    let original = parse_str("# Test (md-decision-trees)
+
+D: Decision Node
+* O: Option 1
+  * P,1,3: Pro for Options 1 and 2
+* O: Option 2
+  * C: Con for Option 2
+* O: Option 3
+  * P,3-C,1,5: Pro for Option 2, Con for Options 1 and 3").unwrap();
+    let mut nodes = original.clone();
+    let mut history = History::default();
+    // Option 2 (3) goes with its Con - the comparative nodes 2 & 6 link to it
+    history.apply(&mut nodes, Op::DeleteNode{file_order: 3}).unwrap();
+    assert_eq!(nodes.nodes.len(), original.nodes.len() - 2);
+    assert!(nodes.nodes.iter().enumerate().all(|(idx, node)| node.file_order == idx as u32));
+    assert_eq!(nodes.nodes[2].parent_idxs, vec![1]); //< Was P,1,3
+    assert_eq!(nodes.nodes[4].type_is, Some(NodeType::Con)); //< Was P,3-C,1,5
+    assert_eq!(nodes.nodes[4].parent_idxs, vec![1, 3]);
+    assert!(nodes.nodes[4].parent_idxs_diff_type.is_empty());
+    let written = nodes_to_string(&nodes, Path::new("deleted.md")).unwrap(); //< Can still be written & read back
+    assert_eq!(parse_str(&written).unwrap(), nodes);
+    assert!(history.undo(&mut nodes).unwrap());
+    assert_eq!(nodes, original);
+  }
+
+  #[test]
+  fn test_delete_with_children() {
+    let original = comparative_nodes();
+    let mut nodes = original.clone();
+    let mut history = History::default();
+    history.apply(&mut nodes, Op::DeleteNode{file_order: 4}).unwrap();
+    assert_eq!(nodes.nodes.iter().map(|node| node.text.as_str()).collect::<Vec<&str>>(),
+      vec!["Decision Node", "Option 1", "Pro for Option 1", "Pro for Options 1 and 2", "Option 3", "Con for Option 3, but Pro for 1 and 2"]);
+    let written = nodes_to_string(&nodes, Path::new("deleted.md")).unwrap();
+    assert_eq!(parse_str(&written).unwrap(), nodes); //< None of Option 2's children are left to be re-parented
+    assert!(history.undo(&mut nodes).unwrap());
+    assert_eq!(nodes, original);
+    assert!(history.redo(&mut nodes).unwrap());
+    assert_eq!(nodes.nodes.len(), 6);
+  }
+
+  #[test]
+  fn test_insert_above_children() {
+    let mut nodes = comparative_nodes();
+    let mut history = History::default();
+    let option = Node::new("Option 1.5".to_string(), NodeType::Option, 3, 1, vec![0], vec![]);
+    assert!(history.apply(&mut nodes, Op::InsertNode{node: option}).is_err()); //< Would take Option 1's second Pro
+    assert_eq!(nodes, comparative_nodes());
+  }
+
+  #[test]
+  fn test_insert_and_batch() {
+    let original = comparative_nodes();
+    let mut nodes = original.clone();
+    let mut history = History::default();
+    let new_pro = Node::new("New Pro".to_string(), NodeType::Pro, 2, 2, vec![1], vec![]);
+    history.apply(&mut nodes, Op::Batch{ops: vec![
+      Op::InsertNode{node: new_pro},
+      Op::SetLinks{file_order: 9, parent_idxs: vec![8], parent_idxs_diff_type: vec![1, 2]},
+    ]}).unwrap();
+    assert_eq!(nodes.nodes[2].text, "New Pro");
+    assert_eq!(nodes.nodes[4].parent_idxs, vec![1, 5]); //< P,1,4 shifted to P,1,5
+    assert_eq!(nodes.nodes[9].parent_idxs_diff_type, vec![1, 2]);
+    assert_eq!(history.summary().undo, vec!["2 changes"]);
+    assert!(history.undo(&mut nodes).unwrap());
+    assert_eq!(nodes, original);
+
+    // A failing batch leaves the nodes as they were
+    let res = history.apply(&mut nodes, Op::Batch{ops: vec![
+      Op::EditText{file_order: 0, text: "Changed".to_string()},
+      Op::DeleteNode{file_order: 99},
+    ]});
+    assert!(res.is_err());
+    assert_eq!(nodes, original);
+  }
+
+  #[test]
+  fn test_history_limit() {
+    let mut nodes = comparative_nodes();
+    let mut history = History::new(2);
+    for text in ["a", "b", "c"] {
+      history.apply(&mut nodes, Op::EditText{file_order: 0, text: text.to_string()}).unwrap();
+    }
+    assert!(history.undo(&mut nodes).unwrap());
+    assert!(history.undo(&mut nodes).unwrap());
+    assert!(!history.undo(&mut nodes).unwrap());
+    assert_eq!(nodes.nodes[0].text, "a");
+  }
+}
@@ -0,0 +1,161 @@
+use crate::file_parse::content_hash;
+use crate::file_write::{nodes_to_file_content, write_file_atomic, SaveOptions};
+use crate::structs::Nodes;
+use serde::{Serialize, Deserialize};
+
+use std::error::Error;
+use std::fs::{create_dir_all, metadata, read_to_string, remove_file};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Autosaved nodes that haven't been saved to their file yet
+#[derive(Serialize, Deserialize)]
+struct JournalEntry {
+  file_path: String,
+  saved_at: u32, //< Unix seconds - u64 isn't allowed in bindings
+  nodes: Nodes,
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub enum DiffLineKind { Same, Added, Removed }
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct DiffLine {
+  pub kind: DiffLineKind,
+  pub text: String,
+}
+
+/// Unsaved nodes newer than the file on disk - offered to the user to restore
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct Recovery {
+  pub nodes: Nodes,
+  pub saved_at: u32,
+  pub diff: Vec<DiffLine>, //< From the file on disk to the unsaved nodes
+}
+
+/// One autosave per file, kept in the app's data directory until the file is saved
+pub struct Journal {
+  dir: PathBuf,
+}
+
+impl Journal {
+  pub fn new(dir: PathBuf) -> Self { Journal{dir} }
+
+  fn entry_path(&self, file_path: &Path) -> PathBuf {
+    return self.dir.join(format!("{}.json", content_hash(&file_path.to_string_lossy())));
+  }
+
+  pub fn autosave(&self, file_path: &Path, nodes: Nodes) -> Result<(), Box<dyn Error>> {
+    create_dir_all(&self.dir)?;
+    let entry = JournalEntry{file_path: file_path.to_string_lossy().to_string(), saved_at: unix_secs(SystemTime::now()), nodes};
+    return write_file_atomic(&self.entry_path(file_path), &serde_json::to_string(&entry)?, &SaveOptions::default());
+  }
+
+  /// Drop the autosave - ex. once the file itself was saved
+  pub fn clear(&self, file_path: &Path) -> Result<(), Box<dyn Error>> {
+    match remove_file(self.entry_path(file_path)) {
+      Err(err) if err.kind() != ErrorKind::NotFound => Err(err.into()),
+      _ => Ok(()),
+    }
+  }
+
+  /// The autosave if its newer than the file & would actually change it
+  pub fn recovery(&self, file_path: &Path) -> Result<Option<Recovery>, Box<dyn Error>> {
+    let entry: JournalEntry = match read_to_string(self.entry_path(file_path)) {
+      Ok(json) => serde_json::from_str(&json)?,
+      Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+      Err(err) => return Err(err.into()),
+    };
+    let (disk_content, disk_saved_at) = match metadata(file_path) {
+      Ok(file_metadata) => (read_to_string(file_path)?, unix_secs(file_metadata.modified()?)),
+      Err(err) if err.kind() == ErrorKind::NotFound => (String::new(), 0),
+      Err(err) => return Err(err.into()),
+    };
+    let unsaved_content = nodes_to_file_content(&entry.nodes, file_path)?;
+    if entry.saved_at < disk_saved_at || unsaved_content == disk_content {
+      return Ok(None);
+    }
+    return Ok(Some(Recovery{diff: diff_lines(&disk_content, &unsaved_content), nodes: entry.nodes, saved_at: entry.saved_at}));
+  }
+}
+
+fn unix_secs(time: SystemTime) -> u32 {
+  return time.duration_since(UNIX_EPOCH).map_or(0, |since| u32::try_from(since.as_secs()).unwrap_or(u32::MAX));
+}
+
+/// Line diff using the longest common subsequence - files are small enough for the O(n*m) table
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+  let old_lines: Vec<&str> = old.lines().collect();
+  let new_lines: Vec<&str> = new.lines().collect();
+  let (n, m) = (old_lines.len(), new_lines.len());
+  // lcs[i][j] - length of the LCS of old_lines[i..] & new_lines[j..]
+  let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+  for i in (0..n).rev() {
+    for j in (0..m).rev() {
+      lcs[i][j] = if old_lines[i] == new_lines[j] { lcs[i+1][j+1] + 1 } else { lcs[i+1][j].max(lcs[i][j+1]) };
+    }
+  }
+  let line = |kind: DiffLineKind, text: &str| DiffLine{kind, text: text.to_string()};
+  let mut diff = Vec::new();
+  let (mut i, mut j) = (0, 0);
+  while i < n || j < m {
+    if i < n && j < m && old_lines[i] == new_lines[j] {
+      diff.push(line(DiffLineKind::Same, old_lines[i]));
+      i += 1; j += 1;
+    } else if j < m && (i == n || lcs[i][j+1] >= lcs[i+1][j]) {
+      diff.push(line(DiffLineKind::Added, new_lines[j]));
+      j += 1;
+    } else {
+      diff.push(line(DiffLineKind::Removed, old_lines[i]));
+      i += 1;
+    }
+  }
+  return diff;
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::file_parse::parse_str;
+  use crate::file_write::write_nodes_to_file;
+  use crate::test_utils::scratch_dir;
+  use std::fs;
+
+  #[test]
+  fn test_diff_lines() {
+    let diff = diff_lines("a\nb\nc", "a\nc\nd");
+    let kinds: Vec<DiffLineKind> = diff.iter().map(|line| line.kind).collect();
+    assert_eq!(kinds, vec![DiffLineKind::Same, DiffLineKind::Removed, DiffLineKind::Same, DiffLineKind::Added]);
+    assert_eq!(diff[1].text, "b");
+    assert_eq!(diff[3].text, "d");
+  }
+
+  #[test]
+  fn test_recovery() {
+    let dir = scratch_dir("journal", "recovery");
+    let file_path = dir.join("decision.md");
+    let journal = Journal::new(dir.join("journal"));
+    let saved = "# Test (md-decision-trees)\n\nD: Saved";
+    fs::write(&file_path, saved).unwrap();
+    assert!(journal.recovery(&file_path).unwrap().is_none());
+
+    // Same content as the file - nothing to offer
+    journal.autosave(&file_path, parse_str(saved).unwrap()).unwrap();
+    assert!(journal.recovery(&file_path).unwrap().is_none());
+
+    journal.autosave(&file_path, parse_str("# Test (md-decision-trees)\n\nD: Unsaved").unwrap()).unwrap();
+    let recovery = journal.recovery(&file_path).unwrap().expect("Should offer the unsaved nodes");
+    assert_eq!(recovery.nodes.nodes[0].text, "Unsaved");
+    assert!(recovery.diff.contains(&DiffLine{kind: DiffLineKind::Removed, text: "D: Saved".to_string()}));
+    assert!(recovery.diff.contains(&DiffLine{kind: DiffLineKind::Added, text: "D: Unsaved".to_string()}));
+
+    write_nodes_to_file(recovery.nodes, file_path.clone()).unwrap();
+    journal.clear(&file_path).unwrap();
+    assert!(journal.recovery(&file_path).unwrap().is_none());
+    journal.clear(&file_path).unwrap(); //< Clearing twice is fine
+  }
+}
@@ -0,0 +1,85 @@
+//! Markdown Decision Trees - parse, check, query & write `(md-decision-trees)` files
+//!
+//! Everything the app does with decision files lives here, so the `mdt` command line tool, the language server
+//! & any other tools read / write files exactly like the app does.
+//!
+//! ```
+//! use mdt_core::file_parse::parse_str;
+//! use mdt_core::file_write::nodes_to_string;
+//! use mdt_core::query::Query;
+//!
+//! let content = "# Database (md-decision-trees)\n\nD: Which database?\n* O: Postgres\n  * P: Mature\n* O: SQLite";
+//! let nodes = parse_str(content).unwrap();
+//! assert_eq!(Query::parse("options where pros > 0").unwrap().run(&nodes), vec![1]);
+//! assert_eq!(nodes_to_string(&nodes, std::path::Path::new("database.md")).unwrap(), content);
+//! ```
+//!
+//! Features:
+//! * `parallel` (default) - parse a workspace's files in parallel
+//! * `git` (default) - load a file's history & its nodes at any revision
+//! * `specta` - derive `specta::Type` for the app's typescript bindings
+
+// Reading & writing files
+/// Read a file into [`structs::Nodes`]
+pub mod file_parse;
+/// Write [`structs::Nodes`] back out - atomically, keeping the file's line endings
+pub mod file_write;
+/// Decision files with `{{placeholders}}` to start new ones from - built in & the user's own
+pub mod templates;
+/// A project's `.mdt.toml` - indent width, node type tokens & colors, validation rules & save defaults
+pub mod config;
+/// Which files the app may read / write - ones the user picked & the open workspace
+pub mod scope;
+/// The tree model - a file's [`structs::Node`]s in file order
+pub mod structs;
+/// Parsers for each line format
+pub mod parsers {
+  /// Bullet point files - a node per bullet, with the node's type & comparative links before its text
+  pub mod bullet_file_parser;
+}
+
+// Checking & querying
+/// `#tags`, `@owners` & decision status from a node's text
+pub mod node_meta;
+/// Inline markdown in a node's text - `**bold**`, `` `code` `` & `[links](url)` as a span tree for the UI
+pub mod rich_text;
+/// Problems in already parsed nodes - ex. comparative indexes pointing at nothing
+pub mod validate;
+/// Children & Pro / Con links of each node
+pub mod graph;
+/// Filter language over the decision graph - ex. `options where pros > cons`
+pub mod query;
+/// Ranked text search with tag / owner / status filters
+pub mod search;
+/// A directory of decision files, parsed together
+pub mod workspace;
+/// Unvalidated assumptions & high risks across files - what to revisit when an assumption breaks
+pub mod tracking;
+/// Expected values of Options through Chance nodes & the optimal path - formal decision analysis
+pub mod analysis;
+/// Option scores from the weights of their Pros & Cons
+pub mod scoring;
+/// Thresholds where a Decision's recommended Option flips & tornado chart data
+pub mod sensitivity;
+/// Pairwise comparisons (Analytic Hierarchy Process) - priorities & consistency of Pros / Cons or Options
+pub mod ahp;
+/// `[ ]` / `[x]` checkboxes in nodes - open follow ups with their owners & due dates
+pub mod action_items;
+
+// Versions of a file
+/// Node level changes between two versions of a file
+pub mod diff;
+/// Three-way merge of decision files
+pub mod merge;
+/// Undo / redo over the tree model
+pub mod history;
+/// Autosaves of unsaved nodes, to recover after a crash
+pub mod journal;
+/// A file's commits & nodes at any revision
+#[cfg(feature = "git")]
+pub mod git_history;
+/// Where each node & comparative index is in the file's text - for editors
+pub mod source_map;
+
+#[cfg(test)]
+mod test_utils;
@@ -0,0 +1,424 @@
+use crate::config::Config;
+use crate::diff::match_nodes;
+use crate::file_write::{node_to_line_under, ImplicitParents};
+use crate::structs::{Node, Nodes, NodeType};
+use serde::{Serialize, Deserialize};
+use std::error::Error;
+
+pub const CONFLICT_START: &str = "<<<<<<< ours";
+pub const CONFLICT_SEPARATOR: &str = "=======";
+pub const CONFLICT_END: &str = ">>>>>>> theirs";
+
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub enum ConflictKind {
+  /// Both sides changed the text or type differently
+  Edited,
+  /// One side deleted the node while the other changed it or added under it - the node is kept
+  DeletedAndEdited,
+  /// Both sides moved the node to different parents - ours is kept
+  Moved,
+}
+
+/// Versions use the merged file orders & levels - None if that side deleted the node
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct MergeConflict {
+  pub file_order: u32,
+  pub kind: ConflictKind,
+  pub ours: Option<Node>,
+  pub theirs: Option<Node>,
+}
+
+/// Merged nodes use ours wherever there was a conflict
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct MergeResult {
+  pub nodes: Nodes,
+  pub conflicts: Vec<MergeConflict>,
+}
+
+/// A version of a node with its parent & links as merged ids - so versions from each side compare directly
+#[derive(Clone, PartialEq, Debug)]
+struct Content {
+  text: String,
+  type_is: Option<NodeType>,
+  parent: Option<usize>, //< Structural parent from the indentation
+  links: Vec<(usize, bool)>, //< Comparative links & if they are diff_type - empty if only the structural parent
+}
+
+struct MergedNode {
+  base: Option<Content>,
+  ours: Option<Content>,
+  theirs: Option<Content>,
+  content: Content,
+  parent_from_theirs: bool,
+  alive: bool,
+  conflict: Option<ConflictKind>,
+}
+
+/// Parent of each node from the indentation - the closest node above with a lower level
+fn structural_parents(nodes: &Nodes) -> Vec<Option<usize>> {
+  let mut parents = Vec::new();
+  let mut stack: Vec<usize> = Vec::new();
+  for (idx, node) in nodes.nodes.iter().enumerate() {
+    while stack.last().is_some_and(|top| nodes.nodes[*top].level >= node.level) { stack.pop(); }
+    parents.push(stack.last().copied());
+    stack.push(idx);
+  }
+  return parents;
+}
+
+/// One version of the file - ids maps its indexes to merged ids
+struct Side<'a> {
+  nodes: &'a Nodes,
+  parents: Vec<Option<usize>>,
+  ids: Vec<usize>,
+}
+
+impl Side<'_> {
+  fn new(nodes: &Nodes) -> Side<'_> {
+    return Side{nodes, parents: structural_parents(nodes), ids: Vec::new()};
+  }
+
+  fn content(&self, idx: usize) -> Content {
+    let node = &self.nodes.nodes[idx];
+    let parent = self.parents[idx];
+    let only_structural = node.parent_idxs_diff_type.is_empty()
+      && (node.parent_idxs.is_empty() || (node.parent_idxs.len() == 1 && Some(node.parent_idxs[0] as usize) == parent));
+    let id_of = |link_idx: &u32| self.ids.get(*link_idx as usize).copied();
+    let links = if only_structural { Vec::new() } else {
+      node.parent_idxs.iter().filter_map(id_of).map(|id| (id, false))
+        .chain(node.parent_idxs_diff_type.iter().filter_map(id_of).map(|id| (id, true)))
+        .collect()
+    };
+    return Content{text: node.text.clone(), type_is: node.type_is.clone(), parent: parent.map(|parent| self.ids[parent]), links};
+  }
+}
+
+/// None if both sides changed the value differently
+fn merge_value<T: PartialEq + Clone>(base: Option<&T>, ours: &T, theirs: &T) -> Option<T> {
+  if ours == theirs || base == Some(theirs) { return Some(ours.clone()); }
+  if base == Some(ours) { return Some(theirs.clone()); }
+  return None;
+}
+
+/// Links are merged as sets - each side's additions are kept & each side's removals are dropped
+fn merge_links(base: &[(usize, bool)], ours: &[(usize, bool)], theirs: &[(usize, bool)]) -> Vec<(usize, bool)> {
+  let mut merged: Vec<(usize, bool)> = ours.iter().filter(|link| theirs.contains(link) || !base.contains(link)).copied().collect();
+  merged.extend(theirs.iter().filter(|link| !ours.contains(link) && !base.contains(link)));
+  return merged;
+}
+
+/// Give every node across the three versions a merged id - matched nodes share one
+fn assign_ids(base: &mut Side, ours: &mut Side, theirs: &mut Side) -> usize {
+  let base_to_ours = match_nodes(base.nodes, ours.nodes);
+  let base_to_theirs = match_nodes(base.nodes, theirs.nodes);
+  ours.ids = vec![usize::MAX; ours.nodes.nodes.len()];
+  theirs.ids = vec![usize::MAX; theirs.nodes.nodes.len()];
+  base.ids = (0..base.nodes.nodes.len()).collect();
+  for (base_idx, ours_idx) in base_to_ours.iter().enumerate() {
+    if let Some(ours_idx) = ours_idx { ours.ids[*ours_idx as usize] = base_idx; }
+  }
+  for (base_idx, theirs_idx) in base_to_theirs.iter().enumerate() {
+    if let Some(theirs_idx) = theirs_idx { theirs.ids[*theirs_idx as usize] = base_idx; }
+  }
+  let mut num_ids = base.ids.len();
+  let mut ours_added = Vec::new();
+  for ours_idx in 0..ours.ids.len() {
+    if ours.ids[ours_idx] != usize::MAX { continue; }
+    ours.ids[ours_idx] = num_ids;
+    ours_added.push(ours_idx);
+    num_ids += 1;
+  }
+  // The same node added on both sides under the same parent is one node
+  for theirs_idx in 0..theirs.ids.len() {
+    if theirs.ids[theirs_idx] != usize::MAX { continue; }
+    let node = &theirs.nodes.nodes[theirs_idx];
+    let parent_id = theirs.parents[theirs_idx].map(|parent| theirs.ids[parent]);
+    let same_added = ours_added.iter().position(|ours_idx| {
+      let ours_node = &ours.nodes.nodes[*ours_idx];
+      ours_node.text == node.text && ours_node.type_is == node.type_is && ours.parents[*ours_idx].map(|parent| ours.ids[parent]) == parent_id
+    });
+    theirs.ids[theirs_idx] = match same_added {
+      Some(pos) => ours.ids[ours_added.remove(pos)],
+      None => { num_ids += 1; num_ids - 1 },
+    };
+  }
+  return num_ids;
+}
+
+fn merge_node(base: Option<Content>, ours: Option<Content>, theirs: Option<Content>) -> MergedNode {
+  let mut merged = MergedNode{
+    content: Content{text: String::new(), type_is: None, parent: None, links: Vec::new()},
+    base: base.clone(), ours: ours.clone(), theirs: theirs.clone(),
+    parent_from_theirs: false, alive: true, conflict: None,
+  };
+  match (base, ours, theirs) {
+    (base, Some(ours), Some(theirs)) => {
+      let text = merge_value(base.as_ref().map(|base| &base.text), &ours.text, &theirs.text);
+      let type_is = merge_value(base.as_ref().map(|base| &base.type_is), &ours.type_is, &theirs.type_is);
+      if text.is_none() || type_is.is_none() { merged.conflict = Some(ConflictKind::Edited); }
+      let parent = merge_value(base.as_ref().map(|base| &base.parent), &ours.parent, &theirs.parent);
+      if parent.is_none() { merged.conflict = merged.conflict.or(Some(ConflictKind::Moved)); }
+      merged.parent_from_theirs = parent.is_some_and(|parent| parent != ours.parent);
+      let base_links = base.as_ref().map_or(Vec::new(), |base| base.links.clone());
+      merged.content = Content{
+        text: text.unwrap_or(ours.text.clone()),
+        type_is: type_is.unwrap_or(ours.type_is),
+        parent: parent.unwrap_or(ours.parent),
+        links: merge_links(&base_links, &ours.links, &theirs.links),
+      };
+    },
+    (Some(base), Some(kept), None) | (Some(base), None, Some(kept)) => {
+      // Deleted on one side - unless the other side changed it
+      merged.alive = kept != base;
+      merged.parent_from_theirs = merged.ours.is_none();
+      if merged.alive { merged.conflict = Some(ConflictKind::DeletedAndEdited); }
+      merged.content = kept;
+    },
+    (None, Some(added), None) | (None, None, Some(added)) => {
+      merged.parent_from_theirs = merged.ours.is_none();
+      merged.content = added;
+    },
+    (Some(_), None, None) | (None, None, None) => merged.alive = false,
+  }
+  return merged;
+}
+
+/// Keep deleted nodes that still have nodes under them - returns if anything changed
+fn keep_needed_parents(merged: &mut [MergedNode]) -> bool {
+  let mut changed = false;
+  for id in 0..merged.len() {
+    let Some(parent) = merged[id].content.parent.filter(|_| merged[id].alive) else { continue; };
+    if merged[parent].alive { continue; }
+    let parent_node = &mut merged[parent];
+    let content = parent_node.ours.clone().or(parent_node.theirs.clone()).or(parent_node.base.clone());
+    parent_node.parent_from_theirs = parent_node.ours.is_none() && parent_node.theirs.is_some();
+    parent_node.content = content.expect("Every merged node has at least one version");
+    parent_node.alive = true;
+    parent_node.conflict = Some(ConflictKind::DeletedAndEdited);
+    changed = true;
+  }
+  return changed;
+}
+
+/// Moves from each side can form a cycle - revert one of theirs to ours - returns if anything changed
+fn break_parent_cycle(merged: &mut [MergedNode]) -> bool {
+  for start in 0..merged.len() {
+    if !merged[start].alive { continue; }
+    // More steps than nodes means the walk is stuck in a cycle
+    let mut curr = start;
+    for _ in 0..merged.len() {
+      match merged[curr].content.parent { Some(parent) => curr = parent, None => break }
+    }
+    if merged[curr].content.parent.is_none() { continue; }
+    for _ in 0..merged.len() {
+      if merged[curr].parent_from_theirs && merged[curr].ours.is_some() { break; }
+      curr = merged[curr].content.parent.unwrap_or(curr);
+    }
+    let reverted = &mut merged[curr];
+    reverted.content.parent = if reverted.parent_from_theirs { reverted.ours.as_ref().and_then(|ours| ours.parent) } else { None };
+    reverted.parent_from_theirs = false;
+    reverted.conflict = reverted.conflict.or(Some(ConflictKind::Moved));
+    return true;
+  }
+  return false;
+}
+
+/// Children of each merged id in order - ours order first, then theirs after its closest sibling already placed
+fn order_children(merged: &[MergedNode], ours: &Side, theirs: &Side) -> (Vec<usize>, Vec<Vec<usize>>) {
+  let mut roots: Vec<usize> = Vec::new();
+  let mut children: Vec<Vec<usize>> = vec![Vec::new(); merged.len()];
+  let mut placed = vec![false; merged.len()];
+  for (ours_idx, id) in ours.ids.iter().enumerate() {
+    let node = &merged[*id];
+    if !node.alive || node.parent_from_theirs || node.content.parent != ours.parents[ours_idx].map(|parent| ours.ids[parent]) { continue; }
+    match node.content.parent { Some(parent) => children[parent].push(*id), None => roots.push(*id) }
+    placed[*id] = true;
+  }
+  let mut place = |id: usize, prev_siblings: &[usize], placed: &mut Vec<bool>| {
+    let siblings = match merged[id].content.parent { Some(parent) => &mut children[parent], None => &mut roots };
+    let pos = prev_siblings.iter().rev().find_map(|sibling| siblings.iter().position(|placed_id| placed_id == sibling)).map_or(0, |pos| pos + 1);
+    siblings.insert(pos, id);
+    placed[id] = true;
+  };
+  for (theirs_idx, id) in theirs.ids.iter().enumerate() {
+    if !merged[*id].alive || placed[*id] { continue; }
+    let theirs_parent = theirs.parents[theirs_idx];
+    let prev_siblings: Vec<usize> = (0..theirs_idx).filter(|idx| theirs.parents[*idx] == theirs_parent).map(|idx| theirs.ids[idx]).collect();
+    place(*id, &prev_siblings, &mut placed);
+  }
+  // Anything left only has ours for a position - ex. kept after theirs deleted it
+  for id in 0..merged.len() {
+    if merged[id].alive && !placed[id] { place(id, &[], &mut placed); }
+  }
+  return (roots, children);
+}
+
+/// Three-way merge of a decision file - nodes are matched across versions, so renumbered comparative indexes merge cleanly
+pub fn merge(base: &Nodes, ours: &Nodes, theirs: &Nodes) -> MergeResult {
+  let (mut base_side, mut ours_side, mut theirs_side) = (Side::new(base), Side::new(ours), Side::new(theirs));
+  let num_ids = assign_ids(&mut base_side, &mut ours_side, &mut theirs_side);
+  let mut versions: Vec<(Option<Content>, Option<Content>, Option<Content>)> = vec![(None, None, None); num_ids];
+  for idx in 0..base.nodes.len() { versions[base_side.ids[idx]].0 = Some(base_side.content(idx)); }
+  for idx in 0..ours.nodes.len() { versions[ours_side.ids[idx]].1 = Some(ours_side.content(idx)); }
+  for idx in 0..theirs.nodes.len() { versions[theirs_side.ids[idx]].2 = Some(theirs_side.content(idx)); }
+  let mut merged: Vec<MergedNode> = versions.into_iter().map(|(base, ours, theirs)| merge_node(base, ours, theirs)).collect();
+  while keep_needed_parents(&mut merged) || break_parent_cycle(&mut merged) {}
+
+  // Depth first through the merged tree gives the new file orders & levels
+  let (roots, children) = order_children(&merged, &ours_side, &theirs_side);
+  let mut order: Vec<(usize, u32)> = Vec::new();
+  let mut stack: Vec<(usize, u32)> = roots.iter().rev().map(|id| (*id, 0)).collect();
+  while let Some((id, level)) = stack.pop() {
+    order.push((id, level));
+    stack.extend(children[id].iter().rev().map(|child| (*child, level + 1)));
+  }
+  let mut file_orders: Vec<Option<u32>> = vec![None; num_ids];
+  for (file_order, (id, _)) in order.iter().enumerate() { file_orders[*id] = Some(file_order as u32); }
+
+  let to_node = |content: &Content, file_order: u32, level: u32| -> Node {
+    let structural_parent = content.parent.and_then(|parent| file_orders[parent]);
+    let links_of = |diff_type: bool| -> Vec<u32> {
+      content.links.iter().filter(|link| link.1 == diff_type).filter_map(|link| file_orders[link.0]).collect()
+    };
+    let mut parent_idxs = links_of(false);
+    let mut parent_idxs_diff_type = links_of(true);
+    if parent_idxs.is_empty() { parent_idxs = structural_parent.into_iter().collect(); }
+    if parent_idxs.is_empty() { parent_idxs_diff_type.clear(); } //< Can't be written without a same type link
+    return Node{text: content.text.clone(), type_is: content.type_is.clone(), file_order, level, parent_idxs, parent_idxs_diff_type};
+  };
+  let title = merge_value(Some(&base.title), &ours.title, &theirs.title).unwrap_or(ours.title.clone());
+  let mut result = MergeResult{nodes: Nodes{title, nodes: Vec::new()}, conflicts: Vec::new()};
+  for (file_order, (id, level)) in order.iter().enumerate() {
+    let file_order = file_order as u32;
+    let node = &merged[*id];
+    result.nodes.nodes.push(to_node(&node.content, file_order, *level));
+    if let Some(kind) = node.conflict {
+      // Each side's own text & type, but placed & linked as merged
+      let version = |side: &Option<Content>| side.as_ref().map(|side| to_node(&Content{
+        text: side.text.clone(), type_is: side.type_is.clone(), ..node.content.clone()}, file_order, *level));
+      result.conflicts.push(MergeConflict{file_order, kind, ours: version(&node.ours), theirs: version(&node.theirs)});
+    }
+  }
+  return result;
+}
+
+/// The merged file's content in a project's format - conflicts where the lines differ get git style markers around
+/// each side's line
+pub fn merge_to_string(result: &MergeResult, config: &Config) -> Result<String, Box<dyn Error>> {
+  let mut content = result.nodes.title.clone();
+  let mut implicit_parents = ImplicitParents::default();
+  for node in &result.nodes.nodes {
+    content.push('\n');
+    if node.level == 0 { content.push('\n'); }
+    let implicit_parent = implicit_parents.next(node);
+    let to_line = |node: &Node| node_to_line_under(node, implicit_parent, config);
+    let line = to_line(node)?;
+    let conflict = result.conflicts.iter().find(|conflict| conflict.file_order == node.file_order);
+    let Some(conflict) = conflict else {
+      content.push_str(&line);
+      continue;
+    };
+    let ours_line = conflict.ours.as_ref().map(to_line).transpose()?;
+    let theirs_line = conflict.theirs.as_ref().map(to_line).transpose()?;
+    if ours_line == theirs_line {
+      content.push_str(&line);
+      continue;
+    }
+    content.push_str(CONFLICT_START);
+    content.push('\n');
+    if let Some(ours_line) = ours_line { content.push_str(&ours_line); content.push('\n'); }
+    content.push_str(CONFLICT_SEPARATOR);
+    content.push('\n');
+    if let Some(theirs_line) = theirs_line { content.push_str(&theirs_line); content.push('\n'); }
+    content.push_str(CONFLICT_END);
+  }
+  return Ok(content);
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::file_parse::parse_str;
+
+  const BASE: &str = "# Test (md-decision-trees)
+
+D: Which database?
+* O: Postgres
+  * P: Mature
+  * C: Needs ops time
+* O: SQLite
+  * P,4-C,1: No server to run";
+
+  fn merge_strs(ours: &str, theirs: &str) -> (MergeResult, String) {
+    let result = merge(&parse_str(BASE).unwrap(), &parse_str(ours).unwrap(), &parse_str(theirs).unwrap());
+    let content = merge_to_string(&result, &Config::default()).unwrap();
+    return (result, content);
+  }
+
+  #[test]
+  fn test_clean_merge_renumbers() {
+    // Ours adds a node above everything, shifting every index - theirs adds a comparative Con referencing the old ones
+    let ours = BASE.replace("D: Which database?", "D: Which database?\n* N: Decide by Friday").replace("P,4-C,1", "P,5-C,2");
+    let theirs = format!("{}\n  * C,4-P,1: Single writer", BASE);
+    let (result, content) = merge_strs(&ours, &theirs);
+    assert!(result.conflicts.is_empty());
+    assert_eq!(content, "# Test (md-decision-trees)
+
+D: Which database?
+* N: Decide by Friday
+* O: Postgres
+  * P: Mature
+  * C: Needs ops time
+* O: SQLite
+  * P,5-C,2: No server to run
+  * C,5-P,2: Single writer");
+    assert_eq!(parse_str(&content).unwrap(), result.nodes);
+  }
+
+  #[test]
+  fn test_edits_to_different_nodes() {
+    let ours = BASE.replace("Mature", "Matured");
+    let theirs = BASE.replace("  * C: Needs ops time\n", "").replace("SQLite", "SQLite 3").replace("P,4-C,1", "P,3-C,1");
+    let (result, content) = merge_strs(&ours, &theirs);
+    assert!(result.conflicts.is_empty());
+    assert_eq!(content, "# Test (md-decision-trees)
+
+D: Which database?
+* O: Postgres
+  * P: Matured
+* O: SQLite 3
+  * P,3-C,1: No server to run");
+  }
+
+  #[test]
+  fn test_conflicting_edit() {
+    let (result, content) = merge_strs(&BASE.replace("Mature", "Matured"), &BASE.replace("Mature", "Mature!"));
+    assert_eq!(result.conflicts.len(), 1);
+    assert_eq!(result.conflicts[0].kind, ConflictKind::Edited);
+    assert!(content.contains("* O: Postgres\n<<<<<<< ours\n  * P: Matured\n=======\n  * P: Mature!\n>>>>>>> theirs\n  * C: Needs ops time"));
+  }
+
+  #[test]
+  fn test_delete_and_edit() {
+    // Theirs deleted the Option ours added a Pro under - so the Option is kept as a conflict
+    let ours = BASE.replace("* O: SQLite", "* O: SQLite\n  * P: Easy backups");
+    let theirs = BASE.replace("* O: SQLite\n  * P,4-C,1: No server to run", "");
+    let (result, content) = merge_strs(&ours, theirs.trim_end());
+    assert_eq!(result.conflicts.len(), 1);
+    assert_eq!(result.conflicts[0].kind, ConflictKind::DeletedAndEdited);
+    assert!(result.conflicts[0].theirs.is_none());
+    // The unchanged Pro under it is still deleted
+    assert!(content.ends_with("<<<<<<< ours\n* O: SQLite\n=======\n>>>>>>> theirs\n  * P: Easy backups"));
+  }
+
+  #[test]
+  fn test_same_addition_on_both_sides() {
+    let both = format!("{}\n* O: MySQL", BASE);
+    let (result, content) = merge_strs(&both, &both);
+    assert!(result.conflicts.is_empty());
+    assert_eq!(content, both);
+  }
+}
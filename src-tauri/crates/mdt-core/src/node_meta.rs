@@ -0,0 +1,258 @@
+use serde::{Serialize, Deserialize};
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::str::FromStr;
+
+// For non-const statics
+lazy_static! {
+  // Tags are written inline in a node's text - ex. `D: Which database? #decided #backend`
+  pub static ref TAG_REGEX: Regex = Regex::new(r"(?:^|\s)#([\w\-/]+)").unwrap();
+  // Owners the same way - ex. `N: Need a meeting about this @zach`
+  pub static ref OWNER_REGEX: Regex = Regex::new(r"(?:^|\s)@([\w\-.]+)").unwrap();
+  // A markdown checkbox starting the text makes any node an action item - ex. `N: [ ] Book a meeting`
+  pub static ref TASK_REGEX: Regex = Regex::new(r"^\[([ xX])\](?:\s+|$)").unwrap();
+  // Due dates are a tag - ex. `#due-2026-11-01`
+  pub static ref DUE_DATE_REGEX: Regex = Regex::new(r"^\d{4}-\d{2}-\d{2}$").unwrap();
+  // Decision analysis numbers - ex. `Y: Demand grows p=0.6 value=120`, or `p=60%`
+  pub static ref PROBABILITY_REGEX: Regex = Regex::new(r"(?:^|\s)p=(\d*\.?\d+)(%?)(?:\s|$)").unwrap();
+  pub static ref PAYOFF_REGEX: Regex = Regex::new(r"(?:^|\s)value=(-?\d*\.?\d+)(?:\s|$)").unwrap();
+  // How much a Pro / Con counts towards its Options' scores - ex. `P: Mature weight=3`
+  pub static ref WEIGHT_REGEX: Regex = Regex::new(r"(?:^|\s)weight=(\d*\.?\d+)(?:\s|$)").unwrap();
+  // A pairwise comparison in an `#ahp` block - ex. `N: Reliable vs Ops burden: 3` or `N: Fly vs AWS: 1/2`
+  pub static ref COMPARISON_REGEX: Regex = Regex::new(r"^(.+?)\s+vs\s+(.+?):\s*(1/)?(\d*\.?\d+)$").unwrap();
+}
+
+/// Where a Decision is at - read from a status tag in the Decision's text, otherwise its still Open
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub enum DecisionStatus {
+  #[default]
+  Open,
+  Decided,
+  Deferred,
+}
+
+impl FromStr for DecisionStatus {
+  type Err = (); //< required for FromStr
+  fn from_str(input: &str) -> Result<DecisionStatus, Self::Err> {
+    match input.to_lowercase().as_str() {
+        "open" => Ok(DecisionStatus::Open),
+        "decided" => Ok(DecisionStatus::Decided),
+        "deferred" => Ok(DecisionStatus::Deferred),
+        _ => Err(()),
+    }
+  }
+}
+
+impl DecisionStatus {
+  /// First status tag wins - ex. `#decided`
+  pub fn from_text(text: &str) -> DecisionStatus {
+    return tags(text).iter()
+      .find_map(|tag| DecisionStatus::from_str(tag).ok())
+      .unwrap_or_default();
+  }
+}
+
+/// Whether an Assumption still holds - `#validated` / `#broken` in its text, otherwise it hasn't been checked
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub enum AssumptionStatus {
+  #[default]
+  Unvalidated,
+  Validated,
+  Broken,
+}
+
+impl AssumptionStatus {
+  /// First status tag wins, like `DecisionStatus`
+  pub fn from_text(text: &str) -> AssumptionStatus {
+    return tags(text).iter()
+      .find_map(|tag| match tag.to_lowercase().as_str() {
+        "validated" => Some(AssumptionStatus::Validated),
+        "broken" => Some(AssumptionStatus::Broken),
+        _ => None,
+      })
+      .unwrap_or_default();
+  }
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub enum RiskLevel {
+  Low,
+  #[default]
+  Medium,
+  High,
+}
+
+impl FromStr for RiskLevel {
+  type Err = (); //< required for FromStr
+  fn from_str(input: &str) -> Result<RiskLevel, Self::Err> {
+    match input.to_lowercase().as_str() {
+        "low" => Ok(RiskLevel::Low),
+        "medium" => Ok(RiskLevel::Medium),
+        "high" => Ok(RiskLevel::High),
+        _ => Err(()),
+    }
+  }
+}
+
+/// A Risk's likelihood & impact - ex. `R: Vendor goes under #likelihood-low #impact-high`. Unrated is Medium
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct RiskRating {
+  pub likelihood: RiskLevel,
+  pub impact: RiskLevel,
+}
+
+impl RiskRating {
+  pub fn from_text(text: &str) -> RiskRating {
+    let level_of = |prefix: &str| tags(text).iter()
+      .find_map(|tag| RiskLevel::from_str(tag.to_lowercase().strip_prefix(prefix)?).ok())
+      .unwrap_or_default();
+    return RiskRating{likelihood: level_of("likelihood-"), impact: level_of("impact-")};
+  }
+
+  /// Likelihood x impact, each 1 - 3
+  pub fn score(&self) -> u32 {
+    return (self.likelihood as u32 + 1) * (self.impact as u32 + 1);
+  }
+
+  /// High on one axis & at least Medium on the other
+  pub fn is_high(&self) -> bool {
+    return self.score() >= 6;
+  }
+}
+
+/// An action item's completion state - `[ ]` open, `[x]` done
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub enum TaskState {
+  Open,
+  Done,
+}
+
+impl TaskState {
+  /// None if the text doesn't start with a checkbox
+  pub fn from_text(text: &str) -> Option<TaskState> {
+    let caps = TASK_REGEX.captures(text)?;
+    return Some(if &caps[1] == " " { TaskState::Open } else { TaskState::Done });
+  }
+}
+
+/// Text after the checkbox - the whole text if it has none
+pub fn without_checkbox(text: &str) -> &str {
+  return TASK_REGEX.find(text).map_or(text, |found| &text[found.end()..]);
+}
+
+/// Text with its checkbox ticked / cleared - a text without one gets one added
+pub fn with_task_state(text: &str, state: TaskState) -> String {
+  let checkbox = match state { TaskState::Open => "[ ]", TaskState::Done => "[x]" };
+  let rest = without_checkbox(text);
+  return if rest.is_empty() { checkbox.to_string() } else { format!("{} {}", checkbox, rest) };
+}
+
+/// `YYYY-MM-DD` from a `#due-...` tag - None if there's none. See `malformed_due_dates` for ones in other formats
+pub fn due_date(text: &str) -> Option<String> {
+  return tags(text).into_iter()
+    .find_map(|tag| Some(tag.strip_prefix("due-")?.to_string()).filter(|date| DUE_DATE_REGEX.is_match(date)));
+}
+
+/// `#due-...` tags that aren't `YYYY-MM-DD` - ex. `#due-friday`
+pub fn malformed_due_dates(text: &str) -> Vec<String> {
+  return tags(text).into_iter().filter(|tag| tag.strip_prefix("due-").is_some_and(|date| !DUE_DATE_REGEX.is_match(date))).collect();
+}
+
+/// An Outcome's chance of happening from `p=0.6` / `p=60%` - a fraction either way
+pub fn probability(text: &str) -> Option<f64> {
+  let caps = PROBABILITY_REGEX.captures(text)?;
+  let probability: f64 = caps[1].parse().ok()?;
+  return Some(if caps[2].is_empty() { probability } else { probability / 100.0 });
+}
+
+/// Payoff of reaching a node from `value=120` - costs are negative, ex. `value=-40`
+pub fn payoff(text: &str) -> Option<f64> {
+  return PAYOFF_REGEX.captures(text)?[1].parse().ok();
+}
+
+/// A Pro / Con's `weight=3` - see scoring.rs
+pub fn weight(text: &str) -> Option<f64> {
+  return WEIGHT_REGEX.captures(text)?[1].parse().ok();
+}
+
+/// A `<a> vs <b>: <ratio>` comparison - how many times more `a` matters than `b`, see ahp.rs
+pub fn comparison(text: &str) -> Option<(String, String, f64)> {
+  let caps = COMPARISON_REGEX.captures(text.trim())?;
+  let ratio: f64 = caps[4].parse().ok()?;
+  if ratio <= 0.0 { return None; }
+  let ratio = if caps.get(3).is_some() { 1.0 / ratio } else { ratio };
+  return Some((caps[1].to_string(), caps[2].to_string(), ratio));
+}
+
+/// All `#tags` in a node's text, without the leading `#`
+pub fn tags(text: &str) -> Vec<String> {
+  return TAG_REGEX.captures_iter(text).map(|caps| caps[1].to_string()).collect();
+}
+
+/// All `@owners` in a node's text, without the leading `@`
+pub fn owners(text: &str) -> Vec<String> {
+  return OWNER_REGEX.captures_iter(text).map(|caps| caps[1].trim_end_matches('.').to_string()).collect();
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_tags_and_status() {
+    assert_eq!(tags("Which DB? #decided #backend"), vec!["decided", "backend"]);
+    assert!(tags("Issue#4 is not a tag").is_empty());
+    assert_eq!(DecisionStatus::from_text("Which DB? #backend #Decided"), DecisionStatus::Decided);
+    assert_eq!(DecisionStatus::from_text("Which DB? #deferred"), DecisionStatus::Deferred);
+    assert_eq!(DecisionStatus::from_text("Which DB?"), DecisionStatus::Open);
+    assert_eq!(owners("Need a meeting @zach and @ann.b."), vec!["zach", "ann.b"]);
+    assert!(owners("mail me at me@example.com").is_empty());
+  }
+
+  #[test]
+  fn test_tasks() {
+    assert_eq!(TaskState::from_text("[ ] Book a meeting"), Some(TaskState::Open));
+    assert_eq!(TaskState::from_text("[X] Book a meeting"), Some(TaskState::Done));
+    assert_eq!(TaskState::from_text("[]  Book a meeting"), None);
+    assert_eq!(TaskState::from_text("Book a meeting [ ]"), None);
+    assert_eq!(without_checkbox("[x]  Book a meeting"), "Book a meeting");
+    assert_eq!(with_task_state("[ ] Book a meeting", TaskState::Done), "[x] Book a meeting");
+    assert_eq!(with_task_state("Book a meeting", TaskState::Open), "[ ] Book a meeting");
+    assert_eq!(due_date("[ ] Book a meeting #due-friday #due-2026-11-01"), Some("2026-11-01".to_string()));
+    assert_eq!(malformed_due_dates("[ ] Book a meeting #due-friday #due-2026-11-01"), vec!["due-friday"]);
+  }
+
+  #[test]
+  fn test_analysis_numbers() {
+    assert_eq!(probability("Demand grows p=0.6 value=120"), Some(0.6));
+    assert_eq!(probability("p=60% Demand grows"), Some(0.6));
+    assert_eq!(probability("Demand grows p=.25"), Some(0.25));
+    assert_eq!(probability("Demand grows up=0.6"), None);
+    assert_eq!(payoff("Demand grows p=0.6 value=120"), Some(120.0));
+    assert_eq!(payoff("Build it value=-40.5"), Some(-40.5));
+    assert_eq!(payoff("Build it value=40k"), None);
+    assert_eq!(weight("Mature weight=2.5"), Some(2.5));
+    assert_eq!(weight("Mature weight=-2"), None);
+    assert_eq!(comparison("Reliable vs Ops burden: 3"), Some(("Reliable".to_string(), "Ops burden".to_string(), 3.0)));
+    assert_eq!(comparison("Fly vs AWS: 1/2"), Some(("Fly".to_string(), "AWS".to_string(), 0.5)));
+    assert_eq!(comparison("Fly vs AWS: 0"), None);
+    assert_eq!(comparison("Fly vs AWS"), None);
+  }
+
+  #[test]
+  fn test_assumptions_and_risks() {
+    assert_eq!(AssumptionStatus::from_text("Traffic stays under 1k rps"), AssumptionStatus::Unvalidated);
+    assert_eq!(AssumptionStatus::from_text("Traffic stays under 1k rps #Broken #validated"), AssumptionStatus::Broken);
+    let rating = RiskRating::from_text("Vendor goes under #likelihood-low #impact-High");
+    assert_eq!((rating.likelihood, rating.impact, rating.is_high()), (RiskLevel::Low, RiskLevel::High, false));
+    assert_eq!(RiskRating::from_text("Unrated"), RiskRating{likelihood: RiskLevel::Medium, impact: RiskLevel::Medium});
+    assert!(RiskRating::from_text("Lock in #impact-high").is_high());
+    assert!(!RiskRating::from_text("Lock in #impact-huge").is_high());
+  }
+}
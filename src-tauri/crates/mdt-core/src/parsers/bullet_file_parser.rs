@@ -1,49 +1,60 @@
-pub mod bullet_file_parser {
-
-use super::file_parse::{NUM_SPACES_PER_LEVEL};
-use super::structs::{Node, NodeType};
+use crate::config::Config;
+use crate::structs::{Node, NodeType};
 use std::collections::VecDeque;
 use lazy_static::lazy_static;
 use regex::Regex;
-use std::str::FromStr;
 
 // For non-const statics
 lazy_static! {
-  pub static ref START_NODE_BEGIN_REGEX: Regex = Regex::new(r"\s*\* ").unwrap();
-  pub static ref COMPARATIVE_NODE_REGEX: Regex = Regex::new(r"^([P|C]),(\d+(?:,\d+)*)(?:-([P|C]),(\d+(?:,\d+)*))?$").unwrap();
+  pub static ref START_NODE_BEGIN_REGEX: Regex = Regex::new(r"^\s*\* ").unwrap();
 }
 
+/// Comparative node's (same_type_parent_idxs, diff_type_parent_idxs)
+type ComparativeIdxs = (Vec<u32>, Vec<u32>);
+
 /// Last node read that may be a parent to the current node
 #[derive(Copy, Clone)]
 struct PotentialParent { level: u32, idx: u32 } 
 
 /// Parses bullet point files
-#[derive(Default)]
 pub struct BulletFileParser
 {
   file_order_cnt : u32,
   prev_parsed_text: Option<String>,
   parent_q: VecDeque<PotentialParent>,
   force_node_type: bool,
+  config: Config, //< Indent width & type tokens
+  comparative_regex: Regex,
+}
+
+impl Default for BulletFileParser {
+  fn default() -> Self { BulletFileParser::with_config(&Config::default()) }
 }
 
 /// Parses file with bullet points into node children
 impl BulletFileParser {
-  pub fn new() -> BulletFileParser { return BulletFileParser{..Default::default()}; }
+  pub fn new() -> BulletFileParser { return BulletFileParser::default(); }
+
+  pub fn with_config(config: &Config) -> BulletFileParser {
+    return BulletFileParser{
+      file_order_cnt: 0, prev_parsed_text: None, parent_q: VecDeque::new(), force_node_type: false,
+      config: config.clone(), comparative_regex: config.comparative_regex(),
+    };
+  }
 
   /// Parse a file line & create a node if its a new bullet point
-  pub fn handle_line(&mut self, line: &String) -> Result<Option<Node>, String> {
+  pub fn handle_line(&mut self, line: &str) -> Result<Option<Node>, String> {
     if line.is_empty() { return Ok(None); }
     let start_of_node_match = START_NODE_BEGIN_REGEX.find(line);
     if self.prev_parsed_text.is_none() {
       if start_of_node_match.is_none() { 
-        return Ok(Some(self.create_node(line, 0))); //< Parent node
+        return Ok(Some(self.create_node(line, 0)?)); //< Parent node
       } else {
         let first_bullet_idx = line.find("*").unwrap();
-        let expected_num_spaces = usize::try_from(NUM_SPACES_PER_LEVEL).map_err(|err| err.to_string())?;
+        let expected_num_spaces = usize::try_from(self.config.indent_width).map_err(|err| err.to_string())?;
         let mut indent_level = u32::try_from(first_bullet_idx / expected_num_spaces).map_err(|err| err.to_string())?;
         indent_level += 1;
-        return Ok(Some(self.create_node(&line[first_bullet_idx+2..], indent_level)));
+        return Ok(Some(self.create_node(&line[first_bullet_idx+2..], indent_level)?));
       }
     } else {
       // TODO - implement multi-line bullets
@@ -53,17 +64,18 @@ impl BulletFileParser {
 
   /// Push into to queue to potentially use as parent node later
   fn add_curr_as_pot_parent(&mut self, level: u32) {
-    self.parent_q.push_back(PotentialParent{level: level, idx: self.file_order_cnt});
+    self.parent_q.push_back(PotentialParent{level, idx: self.file_order_cnt});
   }
 
   /// Create nodes with their file order & tie them to their parent nodes using the indent level
-  fn create_node(&mut self, text: &str, indent_level: u32) -> Node {
+  fn create_node(&mut self, text: &str, indent_level: u32) -> Result<Node, String> {
     let mut new_node = Node{level: indent_level, file_order: self.file_order_cnt, ..Default::default()};
-    if let Some((node_type, new_text)) = self.split_node_type_from_string(&text) {
+    if let Some((node_type, new_text)) = self.split_node_type_from_string(text)? {
+      let is_comparative = node_type == NodeType::Pro || node_type == NodeType::Con;
       new_node.type_is = Some(node_type);
       new_node.text = new_text;
-      if node_type == NodeType::Pro || node_type == NodeType::Con {
-        if let Some((same_type_idxs, diff_type_idxs)) = self.parse_comparative_parent_idxs(&text) {
+      if is_comparative {
+        if let Some((same_type_idxs, diff_type_idxs)) = self.parse_comparative_parent_idxs(text)? {
           new_node.parent_idxs = same_type_idxs;
           new_node.parent_idxs_diff_type = diff_type_idxs;
         }
@@ -74,7 +86,7 @@ impl BulletFileParser {
     // A file will have the nodes in DFS order. So can pop through the potential parent queue until we find our current
     // parent. So if its not the current nodes parent, can remove it since it wont be future node's parent either.
     while !self.parent_q.is_empty() {
-      if let Some(ref pot_parent) = self.parent_q.back() {
+      if let Some(pot_parent) = self.parent_q.back() {
         if pot_parent.level < new_node.level {
           // Only add to parent_idxs if we didn't already set it from comparative parsing
           if new_node.parent_idxs.is_empty() {
@@ -91,66 +103,70 @@ impl BulletFileParser {
       self.add_curr_as_pot_parent(new_node.level);
     }
     self.file_order_cnt += 1;
-    return new_node;
+    return Ok(new_node);
   }
 
   /// Parse off optional NodeType and ensure its valid if this is a NodeType file (vs regular bullets)
   /// TODO - skip this string copy and just make text mut
-  fn split_node_type_from_string(&mut self, text: &str) -> Option<(NodeType, String)> {
+  fn split_node_type_from_string(&mut self, text: &str) -> Result<Option<(NodeType, String)>, String> {
     if let Some(first_colon_idx) = text.find(":") {
       let type_str = &text[..first_colon_idx];
-      if let Some(caps) = COMPARATIVE_NODE_REGEX.captures(type_str) {
-        let node_type_char = caps.get(1).unwrap().as_str(); //< Single letter type
-        let node_type = if node_type_char == "P" { NodeType::Pro } else { NodeType::Con };
-        let colon_and_space_size = 2;
-        let new_text = text[first_colon_idx+colon_and_space_size..].to_string();
+      if let Some(caps) = self.comparative_regex.captures(type_str) {
+        let node_type_token = caps.get(1).unwrap().as_str();
+        let node_type = if node_type_token == self.config.token(&NodeType::Pro) { NodeType::Pro } else { NodeType::Con };
         self.force_node_type = true;
-        return Some((node_type, new_text));
+        return Ok(Some((node_type, text_after_type(text, first_colon_idx))));
       }
-      if let Ok(node_type) = NodeType::from_str(type_str) {
+      if let Some(node_type) = self.config.node_type(type_str) {
         self.force_node_type = true;
-        // TODO - make this more robust
-        let colon_and_space_size = 2;
-        return Some((node_type, text[first_colon_idx+colon_and_space_size..].to_string()));
+        return Ok(Some((node_type, text_after_type(text, first_colon_idx))));
       }
     }
     if self.force_node_type {
-      panic!("Line did not have a NodeType, though previous line did - line missing type: '{}'", text);
+      return Err(format!("Line did not have a NodeType, though previous line did - line missing type: '{}'", text));
     }
-    return None;
+    return Ok(None);
   }
   
   /// Parse comparative parent indexes from a node text
   /// Returns (same_type_parent_idxs, diff_type_parent_idxs) if the node is comparative
-  fn parse_comparative_parent_idxs(&self, text: &str) -> Option<(Vec<u32>, Vec<u32>)> {
+  fn parse_comparative_parent_idxs(&self, text: &str) -> Result<Option<ComparativeIdxs>, String> {
     if let Some(first_colon_idx) = text.find(":") {
       let type_str = &text[..first_colon_idx];
-      if let Some(caps) = COMPARATIVE_NODE_REGEX.captures(type_str) {
+      if let Some(caps) = self.comparative_regex.captures(type_str) {
         // Helper function to parse comma-separated indexes
-        let parse_indexes = |s: &str| -> Vec<u32> {
+        let parse_indexes = |s: &str| -> Result<Vec<u32>, String> {
           s.split(',')
-            .filter_map(|s| s.parse::<u32>().ok())
+            .map(|s| s.parse::<u32>().map_err(|_| format!("Comparative index '{}' is too large for an index - line: '{}'", s, text)))
             .collect()
         };
         let same_type_parent_idxs = caps.get(2)
           .map(|m| parse_indexes(m.as_str()))
+          .transpose()?
           .unwrap_or_default();
         let diff_type_parent_idxs = caps.get(4)
           .map(|m| parse_indexes(m.as_str()))
+          .transpose()?
           .unwrap_or_default();
-        return Some((same_type_parent_idxs, diff_type_parent_idxs));
+        return Ok(Some((same_type_parent_idxs, diff_type_parent_idxs)));
       }
     }
-    None
+    Ok(None)
   }
 }
 
+/// Node text after its type's colon - the writer always puts a space after the colon, but hand written files may not
+fn text_after_type(text: &str, colon_idx: usize) -> String {
+  let after_colon = &text[colon_idx+1..];
+  return after_colon.strip_prefix(' ').unwrap_or(after_colon).to_string();
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
-  use super::super::file_parse::{DATA_DIR, parse_file};
+  use crate::file_parse::{DATA_DIR, parse_file};
 
-  fn vecs_match<T: Eq>(a: &Vec<T>, b: &Vec<T>) -> bool {
+  fn vecs_match<T: Eq>(a: &[T], b: &[T]) -> bool {
     if a.len() != b.len() { return false; }
     let num_matching = a.iter().zip(b.iter()).filter(|&(a,b)| a == b).count();
     return num_matching == a.len();
@@ -213,12 +229,56 @@ mod tests {
     assert!(node_res.is_ok());
   }
 
+  #[test]
+  fn test_missing_node_type_is_err() {
+    let mut parser = BulletFileParser::new();
+    assert!(parser.handle_line("D: Decision").is_ok());
+    assert!(parser.handle_line("* Untyped option").is_err());
+  }
+
+  #[test]
+  fn test_configured_format() {
+    let config = Config::from_toml("indent_width = 4\n[node_types.pro]\ntoken = \"+\"").unwrap();
+    let mut parser = BulletFileParser::with_config(&config);
+    parser.handle_line("D: Which database?").unwrap();
+    parser.handle_line("* O: Postgres").unwrap();
+    parser.handle_line("* O: SQLite").unwrap();
+    let pro = parser.handle_line("    * +: Mature").unwrap().unwrap();
+    assert_eq!((pro.type_is, pro.level, pro.parent_idxs.clone()), (Some(NodeType::Pro), 2, vec![2]));
+    let comparative = parser.handle_line("    * +,1-C,2: Fast").unwrap().unwrap();
+    assert_eq!((comparative.parent_idxs, comparative.parent_idxs_diff_type), (vec![1], vec![2]));
+    assert!(parser.handle_line("    * P: Not the configured token").is_err());
+  }
+
+  #[test]
+  fn test_custom_types() {
+    let config = Config::from_toml("[custom_types.question]\ntoken = \"Q\"\nparents = [\"option\"]").unwrap();
+    let mut parser = BulletFileParser::with_config(&config);
+    parser.handle_line("D: Which database?").unwrap();
+    parser.handle_line("* O: SQLite").unwrap();
+    let question = parser.handle_line("  * Q: Write contention?").unwrap().unwrap();
+    assert_eq!((question.type_is, question.text, question.parent_idxs), (Some(NodeType::Custom("question".to_string())), "Write contention?".to_string(), vec![1]));
+    assert!(parser.handle_line("  * Z: Undeclared").is_err());
+  }
+
+  /// Lines found by the round trip property test / fuzzing - see tests/round_trip.rs
+  #[test]
+  fn test_found_edge_cases() {
+    let mut parser = BulletFileParser::new();
+    let decision = parser.handle_line("D: Use a * b").unwrap().unwrap();
+    assert_eq!((decision.level, decision.text.as_str()), (0, "Use a * b"));
+    assert_eq!(parser.handle_line("* O:").unwrap().unwrap().text, "");
+    assert_eq!(parser.handle_line("* O:\u{e9}").unwrap().unwrap().text, "\u{e9}");
+    assert!(parser.handle_line("  * |,1: Not a Con").is_err());
+    assert!(parser.handle_line("  * P,\u{661}: Not an index").is_err());
+  }
+
   #[test]
   fn test_parse_comparative_parent_idxs() {
     let parser = BulletFileParser::new();
 
     let assert_parents_are = |text: &str, same_type: Vec<u32>, diff_type: Vec<u32>| {
-        let result = parser.parse_comparative_parent_idxs(text);
+        let result = parser.parse_comparative_parent_idxs(text).unwrap();
         assert!(result.is_some());
         let (same_type_res, diff_type_res) = result.unwrap();
         assert_eq!(same_type_res, same_type);
@@ -231,8 +291,9 @@ mod tests {
     assert_parents_are("C,1-P,2,3: Con for Option 1, Pro for Options 2 and 3", vec![1], vec![2, 3]);
 
     // Test failure cases - non comparative, and without a colon
-    assert!(parser.parse_comparative_parent_idxs("P: Pro for Option").is_none());
-    assert!(parser.parse_comparative_parent_idxs("P,1-C,2").is_none());
+    assert!(parser.parse_comparative_parent_idxs("P: Pro for Option").unwrap().is_none());
+    assert!(parser.parse_comparative_parent_idxs("P,1-C,2").unwrap().is_none());
+    assert!(parser.parse_comparative_parent_idxs("P,99999999999: Index too large").is_err());
   }
   
   #[test]
@@ -242,7 +303,7 @@ mod tests {
     if let Ok(nodes) = node_res {
       // Find the comparative node
       let comparative_node = nodes.nodes.iter()
-        .find(|node| node.parent_idxs_diff_type.len() > 0)
+        .find(|node| !node.parent_idxs_diff_type.is_empty())
         .expect("No comparative node found");
       
       // Verify it's a Pro node that is also a Con for other nodes
@@ -263,4 +324,3 @@ mod tests {
     }
   }
 }
-}
\ No newline at end of file
@@ -0,0 +1,380 @@
+// Small query language over the decision graph - ex:
+// * `options where pros > cons and decision.status = open`
+// * `cons shared_by >= 2 options`
+// * `nodes where text contains "latency" or tag = perf`
+//
+// query      := node_set ["where"] [or_expr]
+// or_expr    := and_expr ("or" and_expr)*
+// and_expr   := unary ("and" unary)*
+// unary      := "not" unary | "(" or_expr ")" | operand (cmp | "contains") operand
+// operand    := ["decision." | "parent."] field | number | string | word
+
+use crate::graph::Graph;
+use crate::node_meta::{tags, owners, due_date, payoff, probability, weight, DecisionStatus, TaskState};
+use crate::structs::{Node, Nodes, NodeType};
+use crate::workspace::Workspace;
+use serde::{Serialize, Deserialize};
+
+/// Nodes of a file that matched a query
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct QueryMatch {
+  pub file_path: String,
+  pub file_orders: Vec<u32>,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum NodeSet { Decisions, Options, Pros, Cons, Notes, Assumptions, Risks, Chances, Outcomes, Nodes }
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Field { Text, Type, Level, Status, Tag, Owner, Task, Due, Probability, Value, Weight, Pros, Cons, Notes, Options, Decisions, Assumptions, Risks, Children, SharedBy }
+
+/// Which node a field is read from - relative to the node being tested
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Target { This, Decision, Parent }
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum CmpOp { Eq, Ne, Gt, Ge, Lt, Le, Contains }
+
+#[derive(Clone, PartialEq, Debug)]
+enum Value { Num(f64), Str(String), List(Vec<String>) }
+
+#[derive(Clone, PartialEq, Debug)]
+enum Operand { Field(Target, Field), Lit(Value) }
+
+#[derive(Clone, PartialEq, Debug)]
+enum Expr {
+  Cmp(Operand, CmpOp, Operand),
+  And(Box<Expr>, Box<Expr>),
+  Or(Box<Expr>, Box<Expr>),
+  Not(Box<Expr>),
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Query {
+  pub node_set: NodeSet,
+  filter: Option<Expr>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+enum Token { Word(String), Num(f64), Str(String), Op(CmpOp), LParen, RParen }
+
+impl NodeSet {
+  fn from_word(word: &str) -> Option<NodeSet> {
+    match word {
+      "decisions" => Some(NodeSet::Decisions), "options" => Some(NodeSet::Options),
+      "pros" => Some(NodeSet::Pros), "cons" => Some(NodeSet::Cons),
+      "notes" => Some(NodeSet::Notes), "nodes" => Some(NodeSet::Nodes),
+      "assumptions" => Some(NodeSet::Assumptions), "risks" => Some(NodeSet::Risks),
+      "chances" => Some(NodeSet::Chances), "outcomes" => Some(NodeSet::Outcomes),
+      _ => None,
+    }
+  }
+
+  fn contains(&self, node: &Node) -> bool {
+    let wanted = match self {
+      NodeSet::Decisions => NodeType::Decision, NodeSet::Options => NodeType::Option,
+      NodeSet::Pros => NodeType::Pro, NodeSet::Cons => NodeType::Con,
+      NodeSet::Notes => NodeType::Note,
+      NodeSet::Assumptions => NodeType::Assumption, NodeSet::Risks => NodeType::Risk,
+      NodeSet::Chances => NodeType::Chance, NodeSet::Outcomes => NodeType::Outcome,
+      NodeSet::Nodes => return true,
+    };
+    return node.type_is == Some(wanted);
+  }
+}
+
+impl Field {
+  fn from_word(word: &str) -> Option<Field> {
+    match word {
+      "text" => Some(Field::Text), "type" => Some(Field::Type), "level" => Some(Field::Level),
+      "status" => Some(Field::Status), "tag" => Some(Field::Tag), "owner" => Some(Field::Owner),
+      "task" => Some(Field::Task), "due" => Some(Field::Due),
+      "p" => Some(Field::Probability), "value" => Some(Field::Value), "weight" => Some(Field::Weight),
+      "pros" => Some(Field::Pros), "cons" => Some(Field::Cons), "notes" => Some(Field::Notes),
+      "options" => Some(Field::Options), "decisions" => Some(Field::Decisions),
+      "assumptions" => Some(Field::Assumptions), "risks" => Some(Field::Risks),
+      "children" => Some(Field::Children), "shared_by" => Some(Field::SharedBy),
+      _ => None,
+    }
+  }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+  let chars: Vec<char> = input.chars().collect();
+  let mut tokens = Vec::new();
+  let mut i = 0;
+  while i < chars.len() {
+    let c = chars[i];
+    if c.is_whitespace() { i += 1; continue; }
+    let start = i;
+    match c {
+      '(' => { tokens.push(Token::LParen); i += 1; },
+      ')' => { tokens.push(Token::RParen); i += 1; },
+      '"' | '\'' => {
+        let end = chars[i+1..].iter().position(|&other| other == c).ok_or(format!("Unterminated string at {}", start))?;
+        tokens.push(Token::Str(chars[i+1..i+1+end].iter().collect()));
+        i += end + 2;
+      },
+      '=' | '!' | '<' | '>' => {
+        let has_eq = chars.get(i + 1) == Some(&'=');
+        let op = match (c, has_eq) {
+          ('=', _) => CmpOp::Eq, ('!', true) => CmpOp::Ne,
+          ('>', false) => CmpOp::Gt, ('>', true) => CmpOp::Ge,
+          ('<', false) => CmpOp::Lt, ('<', true) => CmpOp::Le,
+          _ => return Err(format!("Unexpected '{}' at {}", c, start)),
+        };
+        // Allow `==` as well as `=`
+        i += if has_eq { 2 } else { 1 };
+        tokens.push(Token::Op(op));
+      },
+      _ if c.is_ascii_digit() => {
+        while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') { i += 1; }
+        let num_str: String = chars[start..i].iter().collect();
+        tokens.push(Token::Num(num_str.parse().map_err(|_| format!("Invalid number '{}' at {}", num_str, start))?));
+      },
+      _ if c.is_alphabetic() || c == '_' => {
+        while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '.' || chars[i] == '-') { i += 1; }
+        tokens.push(Token::Word(chars[start..i].iter().collect::<String>().to_lowercase()));
+      },
+      _ => return Err(format!("Unexpected '{}' at {}", c, start)),
+    }
+  }
+  return Ok(tokens);
+}
+
+/// Recursive descent over the tokens - see the grammar at the top of this file
+struct QueryParser { tokens: Vec<Token>, pos: usize }
+
+impl QueryParser {
+  fn peek(&self) -> Option<&Token> { return self.tokens.get(self.pos); }
+
+  fn next(&mut self) -> Option<Token> {
+    let token = self.tokens.get(self.pos).cloned();
+    self.pos += 1;
+    return token;
+  }
+
+  fn eat_word(&mut self, word: &str) -> bool {
+    if self.peek() == Some(&Token::Word(word.to_string())) {
+      self.pos += 1;
+      return true;
+    }
+    return false;
+  }
+
+  fn parse_or(&mut self) -> Result<Expr, String> {
+    let mut expr = self.parse_and()?;
+    while self.eat_word("or") {
+      expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+    }
+    return Ok(expr);
+  }
+
+  fn parse_and(&mut self) -> Result<Expr, String> {
+    let mut expr = self.parse_unary()?;
+    while self.eat_word("and") {
+      expr = Expr::And(Box::new(expr), Box::new(self.parse_unary()?));
+    }
+    return Ok(expr);
+  }
+
+  fn parse_unary(&mut self) -> Result<Expr, String> {
+    if self.eat_word("not") {
+      return Ok(Expr::Not(Box::new(self.parse_unary()?)));
+    }
+    if self.peek() == Some(&Token::LParen) {
+      self.pos += 1;
+      let expr = self.parse_or()?;
+      if self.next() != Some(Token::RParen) { return Err("Expected ')'".to_string()); }
+      return Ok(expr);
+    }
+    let lhs = self.parse_operand(true)?;
+    let op = match self.next() {
+      Some(Token::Op(op)) => op,
+      Some(Token::Word(word)) if word == "contains" => CmpOp::Contains,
+      other => return Err(format!("Expected a comparison after {:?}, found {:?}", lhs, other)),
+    };
+    let rhs = self.parse_operand(false)?;
+    // `shared_by >= 2 options` reads better than `shared_by >= 2`
+    if lhs == Operand::Field(Target::This, Field::SharedBy) { self.eat_word("options"); }
+    return Ok(Expr::Cmp(lhs, op, rhs));
+  }
+
+  /// Words on the left of a comparison must be fields - on the right, unknown words are plain strings (ex. `open`)
+  fn parse_operand(&mut self, is_lhs: bool) -> Result<Operand, String> {
+    match self.next() {
+      Some(Token::Num(num)) => Ok(Operand::Lit(Value::Num(num))),
+      Some(Token::Str(text)) => Ok(Operand::Lit(Value::Str(text))),
+      Some(Token::Word(word)) => {
+        let (target, field_word) = match word.split_once('.') {
+          Some(("decision", rest)) => (Target::Decision, rest),
+          Some(("parent", rest)) => (Target::Parent, rest),
+          _ => (Target::This, word.as_str()),
+        };
+        match Field::from_word(field_word) {
+          Some(field) => Ok(Operand::Field(target, field)),
+          None if !is_lhs => Ok(Operand::Lit(Value::Str(word))),
+          None => Err(format!("Unknown field '{}'", word)),
+        }
+      },
+      other => Err(format!("Expected a field or value, found {:?}", other)),
+    }
+  }
+}
+
+impl Query {
+  pub fn parse(input: &str) -> Result<Query, String> {
+    let mut parser = QueryParser{tokens: tokenize(input)?, pos: 0};
+    let node_set = match parser.next() {
+      Some(Token::Word(word)) => NodeSet::from_word(&word).ok_or(format!("Unknown node set '{}' - expected decisions, options, pros, cons, notes, assumptions, risks, chances, outcomes or nodes", word))?,
+      _ => return Err("Query must start with a node set - ex. `options`".to_string()),
+    };
+    parser.eat_word("where");
+    let filter = if parser.peek().is_some() { Some(parser.parse_or()?) } else { None };
+    if let Some(token) = parser.peek() {
+      return Err(format!("Unexpected {:?} at the end of the query", token));
+    }
+    return Ok(Query{node_set, filter});
+  }
+
+  /// File orders of the matching nodes - in file order
+  pub fn run(&self, nodes: &Nodes) -> Vec<u32> {
+    let graph = Graph::new(nodes);
+    return nodes.nodes.iter()
+      .filter(|node| self.node_set.contains(node))
+      .filter(|node| self.filter.as_ref().is_none_or(|expr| eval(&graph, node, expr)))
+      .map(|node| node.file_order)
+      .collect();
+  }
+
+  /// Only files with at least one match are returned
+  pub fn run_workspace(&self, workspace: &Workspace) -> Vec<QueryMatch> {
+    return workspace.files.iter()
+      .filter_map(|file| {
+        let file_orders = self.run(file.nodes.as_ref().ok()?);
+        if file_orders.is_empty() { return None; }
+        Some(QueryMatch{file_path: file.path.to_string_lossy().to_string(), file_orders})
+      })
+      .collect();
+  }
+}
+
+fn eval(graph: &Graph, node: &Node, expr: &Expr) -> bool {
+  match expr {
+    Expr::And(lhs, rhs) => eval(graph, node, lhs) && eval(graph, node, rhs),
+    Expr::Or(lhs, rhs) => eval(graph, node, lhs) || eval(graph, node, rhs),
+    Expr::Not(inner) => !eval(graph, node, inner),
+    Expr::Cmp(lhs, op, rhs) => {
+      // A missing target (ex. `parent.` on a top level node) never matches
+      let (Some(lhs), Some(rhs)) = (resolve(graph, node, lhs), resolve(graph, node, rhs)) else { return false; };
+      compare(&lhs, *op, &rhs)
+    },
+  }
+}
+
+fn resolve(graph: &Graph, node: &Node, operand: &Operand) -> Option<Value> {
+  let (target, field) = match operand {
+    Operand::Lit(value) => return Some(value.clone()),
+    Operand::Field(target, field) => (*target, *field),
+  };
+  let node = match target {
+    Target::This => node,
+    Target::Decision => graph.nodes.enclosing_decision(node.file_order)?,
+    Target::Parent => graph.nodes.nodes.get(*node.parent_idxs.first()? as usize)?,
+  };
+  let count_of = |node_type: NodeType| Value::Num(graph.children_of_type(node.file_order, node_type).len() as f64);
+  return Some(match field {
+    Field::Text => Value::Str(node.text.clone()),
+    Field::Type => Value::Str(node.type_is.as_ref().map_or(String::new(), |type_is| type_is.id().to_string())),
+    Field::Level => Value::Num(node.level as f64),
+    Field::Status => {
+      let decision = graph.nodes.enclosing_decision(node.file_order)?;
+      Value::Str(format!("{:?}", DecisionStatus::from_text(&decision.text)))
+    },
+    Field::Tag => Value::List(tags(&node.text)),
+    Field::Owner => Value::List(owners(&node.text)),
+    Field::Task => Value::Str(TaskState::from_text(&node.text).map_or(String::new(), |state| format!("{:?}", state))),
+    Field::Due => Value::Str(due_date(&node.text)?),
+    Field::Probability => Value::Num(probability(&node.text)?),
+    Field::Value => Value::Num(payoff(&node.text)?),
+    Field::Weight => Value::Num(weight(&node.text)?),
+    Field::Pros => count_of(NodeType::Pro),
+    Field::Cons => count_of(NodeType::Con),
+    Field::Notes => count_of(NodeType::Note),
+    Field::Options => count_of(NodeType::Option),
+    Field::Decisions => count_of(NodeType::Decision),
+    Field::Assumptions => count_of(NodeType::Assumption),
+    Field::Risks => count_of(NodeType::Risk),
+    Field::Children => Value::Num(graph.children(node.file_order).len() as f64),
+    Field::SharedBy => Value::Num(graph.linked_options(node.file_order).len() as f64),
+  });
+}
+
+fn compare(lhs: &Value, op: CmpOp, rhs: &Value) -> bool {
+  match (lhs, rhs) {
+    (Value::List(items), other) | (other, Value::List(items)) => {
+      // Lists match if any item does - except != which means none of them are equal
+      if op == CmpOp::Ne { return !items.iter().any(|item| compare(&Value::Str(item.clone()), CmpOp::Eq, other)); }
+      items.iter().any(|item| compare(&Value::Str(item.clone()), op, other))
+    },
+    (Value::Num(lhs), Value::Num(rhs)) => compare_ord(lhs.partial_cmp(rhs), op),
+    (Value::Str(text), Value::Num(num)) => text.parse::<f64>().is_ok_and(|parsed| compare(&Value::Num(parsed), op, &Value::Num(*num))),
+    (Value::Num(num), Value::Str(text)) => text.parse::<f64>().is_ok_and(|parsed| compare(&Value::Num(*num), op, &Value::Num(parsed))),
+    (Value::Str(lhs), Value::Str(rhs)) => {
+      let (lhs, rhs) = (lhs.to_lowercase(), rhs.to_lowercase());
+      if op == CmpOp::Contains { return lhs.contains(&rhs); }
+      compare_ord(Some(lhs.cmp(&rhs)), op)
+    },
+  }
+}
+
+fn compare_ord(ordering: Option<std::cmp::Ordering>, op: CmpOp) -> bool {
+  use std::cmp::Ordering::*;
+  let Some(ordering) = ordering else { return false; };
+  match op {
+    CmpOp::Eq => ordering == Equal, CmpOp::Ne => ordering != Equal,
+    CmpOp::Gt => ordering == Greater, CmpOp::Ge => ordering != Less,
+    CmpOp::Lt => ordering == Less, CmpOp::Le => ordering != Greater,
+    CmpOp::Contains => false, //< Only valid for text
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::file_parse::{DATA_DIR, parse_file};
+
+  fn run(query: &str, file_name: &str) -> Vec<u32> {
+    let nodes = parse_file(DATA_DIR.join(file_name)).unwrap();
+    return Query::parse(query).unwrap().run(&nodes);
+  }
+
+  #[test]
+  fn test_queries() {
+    assert_eq!(run("decisions", "03_basic_encoding.md"), vec![0, 9]);
+    assert_eq!(run("options where pros > cons", "05_comparative_encoding_output.md"), vec![1, 4]);
+    assert_eq!(run("options where pros < cons and decision.status = open", "03_basic_encoding.md"), vec![4]);
+    assert_eq!(run("cons shared_by >= 2 options", "05_comparative_encoding_output.md"), vec![8]);
+    assert_eq!(run("nodes where shared_by >= 2", "05_comparative_encoding_output.md"), vec![3, 6, 8]);
+    assert_eq!(run("nodes where text contains \"meeting\" or (type = con and level > 2)", "03_basic_encoding.md"), vec![12]);
+    assert_eq!(run("notes where not parent.type = decision", "03_basic_encoding.md"), vec![7]);
+    assert!(run("decisions where status = decided", "03_basic_encoding.md").is_empty());
+    assert_eq!(run("options where risks > 0 and assumptions > 0", "06_assumptions_and_risks.md"), vec![2]);
+    assert_eq!(run("assumptions where tag != validated", "06_assumptions_and_risks.md"), vec![4, 5]);
+    assert_eq!(run("nodes where task = open and due < \"2026-12-01\"", "07_action_items.md"), vec![6]);
+    assert_eq!(run("notes where task = done", "07_action_items.md"), vec![3]);
+    assert_eq!(run("outcomes where p >= 0.5 and value > 50", "08_decision_analysis.md"), vec![3, 11]);
+  }
+
+  #[test]
+  fn test_parse_errors() {
+    assert!(Query::parse("").is_err());
+    assert!(Query::parse("widgets").is_err());
+    assert!(Query::parse("options where prso > 1").is_err());
+    assert!(Query::parse("options where pros >").is_err());
+    assert!(Query::parse("options where (pros > 1").is_err());
+    assert!(Query::parse("options where text contains \"x").is_err());
+    assert!(Query::parse("options pros > 1 extra").is_err());
+  }
+}
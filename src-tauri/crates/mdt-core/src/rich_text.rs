@@ -0,0 +1,215 @@
+use serde::{Serialize, Deserialize};
+
+/// Links are only kept with these schemes - any other (ex. `javascript:`) is shown as its label
+const SAFE_URL_SCHEMES: [&str; 3] = ["http://", "https://", "mailto:"];
+
+/// Inline markdown of a node's text - plain strings only, so showing it can't inject any html
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub enum Span {
+  Text { text: String },
+  Bold { children: Vec<Span> }, //< `**bold**`
+  Italic { children: Vec<Span> }, //< `*italic*`
+  Code { text: String }, //< `` `code` `` - nothing inside is parsed
+  Link { url: String, children: Vec<Span> }, //< `[label](https://...)`
+}
+
+/// Parse `**bold**`, `*italic*`, `` `code` `` & `[links](url)` - anything unclosed is kept as text & `\*` escapes
+pub fn spans(text: &str) -> Vec<Span> {
+  let bytes = text.as_bytes();
+  let mut spans = Vec::new();
+  let mut plain = String::new();
+  let mut idx = 0;
+  while idx < text.len() {
+    let rest = &text[idx..];
+    let found = match bytes[idx] {
+      b'\\' if rest[1..].starts_with(|c: char| c.is_ascii_punctuation()) => {
+        plain.push(bytes[idx + 1] as char);
+        idx += 2;
+        continue;
+      },
+      b'`' => code_span(rest),
+      b'[' => link(rest),
+      b'*' => emphasis(rest),
+      _ => None,
+    };
+    match found {
+      Some((found_spans, len)) => {
+        push_text(&mut spans, std::mem::take(&mut plain));
+        for span in found_spans {
+          match span {
+            Span::Text{text} => push_text(&mut spans, text),
+            span => spans.push(span),
+          }
+        }
+        idx += len;
+      },
+      None => {
+        // A whole run of `*` / `` ` `` is text, so part of it isn't tried again as a shorter delimiter
+        let len = match bytes[idx] {
+          b'`' | b'*' => run_length(rest, bytes[idx]),
+          _ => rest.chars().next().map_or(1, char::len_utf8),
+        };
+        plain.push_str(&rest[..len]);
+        idx += len;
+      },
+    }
+  }
+  push_text(&mut spans, plain);
+  return spans;
+}
+
+/// Text without any of the markdown - ex. for measuring or searching
+pub fn plain_text(spans: &[Span]) -> String {
+  return spans.iter().map(|span| match span {
+    Span::Text{text} | Span::Code{text} => text.clone(),
+    Span::Bold{children} | Span::Italic{children} | Span::Link{children, ..} => plain_text(children),
+  }).collect();
+}
+
+/// Adds to the last span if it's text too
+fn push_text(spans: &mut Vec<Span>, text: String) {
+  if text.is_empty() { return; }
+  match spans.last_mut() {
+    Some(Span::Text{text: last}) => last.push_str(&text),
+    _ => spans.push(Span::Text{text}),
+  }
+}
+
+fn run_length(text: &str, byte: u8) -> usize {
+  return text.bytes().take_while(|found| *found == byte).count();
+}
+
+/// Closed by a run of as many backticks - one space is trimmed from each end, so `` ` `x` ` `` shows backticks
+fn code_span(rest: &str) -> Option<(Vec<Span>, usize)> {
+  let bytes = rest.as_bytes();
+  let open = run_length(rest, b'`');
+  let mut idx = open;
+  while idx < rest.len() {
+    if bytes[idx] != b'`' { idx += 1; continue; }
+    let close = run_length(&rest[idx..], b'`');
+    if close == open {
+      let inner = &rest[open..idx];
+      let inner = if inner.len() > 2 && inner.starts_with(' ') && inner.ends_with(' ') { &inner[1..inner.len() - 1] } else { inner };
+      return Some((vec![Span::Code{text: inner.to_string()}], idx + close));
+    }
+    idx += close;
+  }
+  return None;
+}
+
+/// `**` opens bold & `*` italic - the opening can't be followed by whitespace or the closing come after it
+fn emphasis(rest: &str) -> Option<(Vec<Span>, usize)> {
+  let bytes = rest.as_bytes();
+  let delimiter = if run_length(rest, b'*') >= 2 { 2 } else { 1 };
+  if !rest[delimiter..].starts_with(|c: char| !c.is_whitespace()) { return None; }
+  let mut idx = delimiter + 1;
+  while idx < rest.len() {
+    match bytes[idx] {
+      b'\\' => idx += 2,
+      b'*' => {
+        let run = run_length(&rest[idx..], b'*');
+        // A longer run closes this & an emphasis inside it - ex. `**bold *italic***`
+        let closes = if delimiter == 2 { run >= 2 } else { run == 1 || run >= 3 };
+        let close = idx + run - delimiter;
+        if closes && !rest[..close].ends_with(char::is_whitespace) {
+          let children = spans(&rest[delimiter..close]);
+          let span = if delimiter == 2 { Span::Bold{children} } else { Span::Italic{children} };
+          return Some((vec![span], close + delimiter));
+        }
+        idx += run;
+      },
+      _ => idx += 1,
+    }
+  }
+  return None;
+}
+
+/// `[label](url)` - the url can't have whitespace. An unsafe url keeps only the label
+fn link(rest: &str) -> Option<(Vec<Span>, usize)> {
+  let bytes = rest.as_bytes();
+  let mut depth = 0;
+  let mut idx = 0;
+  let label_end = loop {
+    match bytes.get(idx)? {
+      b'\\' => idx += 1,
+      b'[' => depth += 1,
+      b']' => {
+        depth -= 1;
+        if depth == 0 { break idx; }
+      },
+      _ => {},
+    }
+    idx += 1;
+  };
+  if bytes.get(label_end + 1) != Some(&b'(') { return None; }
+  let url_len = url_length(&rest[label_end + 2..])?;
+  let url = &rest[label_end + 2..label_end + 2 + url_len];
+  if url.is_empty() || url.contains(char::is_whitespace) { return None; }
+  let children = spans(&rest[1..label_end]);
+  let len = label_end + 3 + url_len;
+  if !SAFE_URL_SCHEMES.iter().any(|scheme| url.get(..scheme.len()).is_some_and(|start| start.eq_ignore_ascii_case(scheme))) {
+    return Some((children, len));
+  }
+  return Some((vec![Span::Link{url: url.to_string(), children}], len));
+}
+
+/// Up to the `)` closing the link - parentheses inside the url have to be balanced, ex. `(https://w.org/a_(b))`
+fn url_length(rest: &str) -> Option<usize> {
+  let mut depth = 0;
+  for (idx, found) in rest.bytes().enumerate() {
+    match found {
+      b'(' => depth += 1,
+      b')' if depth == 0 => return Some(idx),
+      b')' => depth -= 1,
+      _ => {},
+    }
+  }
+  return None;
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn text(text: &str) -> Span { Span::Text{text: text.to_string()} }
+
+  #[test]
+  fn test_spans() {
+    assert_eq!(spans("Plain #tag @owner"), vec![text("Plain #tag @owner")]);
+    assert_eq!(spans("Use **Postgres** with `pg_dump --clean`"), vec![
+      text("Use "), Span::Bold{children: vec![text("Postgres")]}, text(" with "), Span::Code{text: "pg_dump --clean".to_string()},
+    ]);
+    assert_eq!(spans("See [the *docs*](https://example.com/a_b)."), vec![
+      text("See "),
+      Span::Link{url: "https://example.com/a_b".to_string(), children: vec![text("the "), Span::Italic{children: vec![text("docs")]}]},
+      text("."),
+    ]);
+    assert_eq!(spans("**bold *both***"), vec![Span::Bold{children: vec![text("bold "), Span::Italic{children: vec![text("both")]}]}]);
+    assert_eq!(spans("``a ` b``"), vec![Span::Code{text: "a ` b".to_string()}]);
+    assert_eq!(spans("[Pareto](https://en.wikipedia.org/wiki/Pareto_(disambiguation)))"), vec![
+      Span::Link{url: "https://en.wikipedia.org/wiki/Pareto_(disambiguation)".to_string(), children: vec![text("Pareto")]},
+      text(")"),
+    ]);
+  }
+
+  #[test]
+  fn test_unclosed_and_unsafe() {
+    for unchanged in ["5 * 3 * 2", "**not bold", "`open", "[label] (url)", "[x](has space)", "* not italic*", "snake_case_name"] {
+      assert_eq!(spans(unchanged), vec![text(unchanged)], "{}", unchanged);
+    }
+    assert_eq!(spans(r"\*\*literal\*\*"), vec![text("**literal**")]);
+    assert_eq!(spans("[click](javascript:alert(1))"), vec![text("click")]);
+    assert_eq!(spans("<script>alert(1)</script>"), vec![text("<script>alert(1)</script>")]); //< Only ever text
+    assert_eq!(plain_text(&spans("Use **Postgres** & [`pg`](https://a.b)")), "Use Postgres & pg");
+  }
+
+  proptest::proptest! {
+    // Mostly markdown characters, so unclosed & nested delimiters come up often
+    #[test]
+    fn test_any_text_parses(text in r"[*`\[\]()\\a é:/]{0,24}") {
+      let parsed = spans(&text);
+      proptest::prop_assert!(plain_text(&parsed).len() <= text.len());
+    }
+  }
+}
@@ -0,0 +1,143 @@
+use serde::{Serialize, Deserialize};
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+
+/// Why a path was refused
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub enum ScopeError {
+  /// Relative or containing `..` - paths handed out by a dialog / workspace are always absolute & clean
+  Traversal { path: String },
+  /// Not picked by the user & not under an allowed directory
+  OutsideScope { path: String },
+  /// Its directory doesn't exist or can't be read
+  Unresolvable { path: String, reason: String },
+}
+
+impl fmt::Display for ScopeError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      ScopeError::Traversal{path} => write!(f, "'{}' must be an absolute path without '..'", path),
+      ScopeError::OutsideScope{path} => write!(f, "'{}' wasn't opened in the app or the open workspace", path),
+      ScopeError::Unresolvable{path, reason} => write!(f, "Can't resolve '{}' - {}", path, reason),
+    }
+  }
+}
+
+impl Error for ScopeError {}
+
+/// Files & directories the app may read / write - everything else is refused, whatever the frontend asks for
+#[derive(Default, Debug)]
+pub struct PathScope {
+  files: HashSet<PathBuf>,
+  dirs: Vec<PathBuf>,
+}
+
+impl PathScope {
+  /// Allow a single file - ex. one picked in a dialog. Returns the path commands should then use
+  pub fn allow_file(&mut self, path: &Path) -> Result<PathBuf, ScopeError> {
+    let path = canonical(path)?;
+    self.files.insert(path.clone());
+    return Ok(path);
+  }
+
+  /// Allow everything under a directory - ex. the open workspace
+  pub fn allow_dir(&mut self, dir: &Path) -> Result<PathBuf, ScopeError> {
+    let dir = canonical(dir)?;
+    if !self.dirs.contains(&dir) { self.dirs.push(dir.clone()); }
+    return Ok(dir);
+  }
+
+  /// Path to use for a file the frontend asked for - symlinks are resolved first, so they can't point outside
+  pub fn resolve(&self, path: &str) -> Result<PathBuf, ScopeError> {
+    let resolved = canonical(Path::new(path))?;
+    if self.files.contains(&resolved) || self.dirs.iter().any(|dir| resolved.starts_with(dir)) {
+      return Ok(resolved);
+    }
+    return Err(ScopeError::OutsideScope{path: path.to_string()});
+  }
+}
+
+/// Absolute path with symlinks resolved - the file itself may not exist yet (ex. a new file), but its directory must
+fn canonical(path: &Path) -> Result<PathBuf, ScopeError> {
+  let display = path.to_string_lossy().to_string();
+  if !path.is_absolute() || path.components().any(|component| component == Component::ParentDir) {
+    return Err(ScopeError::Traversal{path: display});
+  }
+  let unresolvable = |err: std::io::Error| ScopeError::Unresolvable{path: display.clone(), reason: err.to_string()};
+  match path.canonicalize() {
+    Ok(resolved) => return Ok(resolved),
+    Err(err) if err.kind() != ErrorKind::NotFound => return Err(unresolvable(err)),
+    Err(_) => {},
+  };
+  let (Some(parent), Some(file_name)) = (path.parent(), path.file_name()) else {
+    return Err(ScopeError::Unresolvable{path: display, reason: "No file name".to_string()});
+  };
+  return Ok(parent.canonicalize().map_err(unresolvable)?.join(file_name));
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_utils::scratch_dir;
+  use std::fs;
+
+  /// Fresh directory under the OS temp dir with an `allowed/` & `other/` directory, each holding a file
+  fn tmp_dirs(name: &str) -> PathBuf {
+    let dir = scratch_dir("scope", name);
+    for sub_dir in ["allowed", "other"] {
+      fs::create_dir_all(dir.join(sub_dir)).unwrap();
+      fs::write(dir.join(sub_dir).join("file.md"), "").unwrap();
+    }
+    return dir.canonicalize().unwrap();
+  }
+
+  fn as_str(path: &Path) -> &str { path.to_str().unwrap() }
+
+  #[test]
+  fn test_allowed_file() {
+    let dir = tmp_dirs("file");
+    let mut scope = PathScope::default();
+    let file = dir.join("other/file.md");
+    assert_eq!(scope.resolve(as_str(&file)), Err(ScopeError::OutsideScope{path: as_str(&file).to_string()}));
+    assert_eq!(scope.allow_file(&file).unwrap(), file);
+    assert_eq!(scope.resolve(as_str(&file)).unwrap(), file);
+    // Only that file - not its neighbours
+    assert!(scope.resolve(as_str(&dir.join("other/new.md"))).is_err());
+  }
+
+  #[test]
+  fn test_allowed_dir() {
+    let dir = tmp_dirs("dir");
+    let mut scope = PathScope::default();
+    scope.allow_dir(&dir.join("allowed")).unwrap();
+    assert!(scope.resolve(as_str(&dir.join("allowed/file.md"))).is_ok());
+    assert!(scope.resolve(as_str(&dir.join("allowed/new.md"))).is_ok()); //< New files are fine
+    assert!(matches!(scope.resolve(as_str(&dir.join("other/file.md"))), Err(ScopeError::OutsideScope{..})));
+    assert!(matches!(scope.resolve(as_str(&dir.join("missing/new.md"))), Err(ScopeError::Unresolvable{..})));
+  }
+
+  #[test]
+  fn test_traversal() {
+    let dir = tmp_dirs("traversal");
+    let mut scope = PathScope::default();
+    scope.allow_dir(&dir.join("allowed")).unwrap();
+    let escape = format!("{}/../other/file.md", as_str(&dir.join("allowed")));
+    assert!(matches!(scope.resolve(&escape), Err(ScopeError::Traversal{..})));
+    assert!(matches!(scope.resolve("allowed/file.md"), Err(ScopeError::Traversal{..})));
+    assert!(matches!(scope.resolve("TEST_FILE:01_bullets.md"), Err(ScopeError::Traversal{..})));
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn test_symlink_out_of_scope() {
+    let dir = tmp_dirs("symlink");
+    let mut scope = PathScope::default();
+    scope.allow_dir(&dir.join("allowed")).unwrap();
+    std::os::unix::fs::symlink(dir.join("other/file.md"), dir.join("allowed/link.md")).unwrap();
+    assert!(matches!(scope.resolve(as_str(&dir.join("allowed/link.md"))), Err(ScopeError::OutsideScope{..})));
+  }
+}
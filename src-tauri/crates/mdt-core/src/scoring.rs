@@ -0,0 +1,57 @@
+use crate::ahp::criteria_weights;
+use crate::graph::Graph;
+use crate::node_meta::weight;
+use crate::structs::{Nodes, NodeType};
+
+/// Unweighted Pros / Cons count this much
+pub const DEFAULT_WEIGHT: f64 = 1.0;
+
+/// Option scores from the weights of their Pros & Cons - ex. `P: Mature weight=3`, or from pairwise comparisons in an
+/// `#ahp` block, which win over written weights. Weights are kept apart from the nodes so they can be changed & scored
+/// again, ex. for sensitivity analysis
+pub struct ScoreModel<'a> {
+  pub graph: Graph<'a>,
+  pub weights: Vec<f64>,
+}
+
+impl<'a> ScoreModel<'a> {
+  pub fn new(nodes: &'a Nodes) -> Self {
+    let graph = Graph::new(nodes);
+    let mut weights: Vec<f64> = nodes.nodes.iter().map(|node| weight(&node.text).unwrap_or(DEFAULT_WEIGHT)).collect();
+    for compared in criteria_weights(&graph) { weights[compared.file_order as usize] = compared.priority; }
+    return ScoreModel{graph, weights};
+  }
+
+  /// Sum of its Pros' weights minus its Cons' - comparative ones count for every Option they're linked to
+  pub fn score(&self, option: u32) -> f64 {
+    let sum_of = |node_type| self.graph.children_of_type(option, node_type).iter().map(|idx| self.weights[*idx as usize]).sum::<f64>();
+    return sum_of(NodeType::Pro) - sum_of(NodeType::Con);
+  }
+
+  /// Highest scoring Option & its score - the first one on a tie
+  pub fn best_option(&self, decision: u32) -> Option<(u32, f64)> {
+    return self.graph.children_of_type(decision, NodeType::Option).into_iter()
+      .map(|option| (option, self.score(option)))
+      .fold(None, |best: Option<(u32, f64)>, (option, score)| match best {
+        Some((_, best_score)) if best_score >= score => best,
+        _ => Some((option, score)),
+      });
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::file_parse::{DATA_DIR, parse_file};
+
+  #[test]
+  fn test_scores() {
+    let nodes = parse_file(DATA_DIR.join("05_comparative_encoding_output.md")).unwrap();
+    let model = ScoreModel::new(&nodes);
+    // 3 Pros (2 shared with Option 2) & the Pro for Option 2 that is a Con for it
+    assert_eq!(model.score(1), 2.0);
+    assert_eq!(model.score(4), 2.0);
+    assert_eq!(model.score(7), -2.0);
+    assert_eq!(model.best_option(0), Some((1, 2.0))); //< First one on a tie
+  }
+}
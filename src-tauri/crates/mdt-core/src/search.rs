@@ -0,0 +1,224 @@
+use crate::node_meta::{tags, owners, DecisionStatus};
+use crate::structs::{Node, Nodes, NodeType};
+use crate::workspace::Workspace;
+use serde::{Serialize, Deserialize};
+
+/// Max characters of node text sent back per hit
+pub const SNIPPET_LEN: usize = 80;
+/// Characters kept before the first highlight when a snippet needs cut down
+const SNIPPET_LEAD: usize = 20;
+/// A fuzzy term can be spread over at most this many times its own length
+const MAX_FUZZY_SPREAD: usize = 3;
+
+/// Everything is optional - an empty query matches every node
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct SearchQuery {
+  pub text: Option<String>, //< Whitespace separated terms - each must match exactly or fuzzily
+  pub node_types: Vec<NodeType>, //< Any of these types, or any type if empty
+  pub status: Option<DecisionStatus>, //< Status of the Decision the node falls under
+  pub tag: Option<String>,
+  pub owner: Option<String>,
+  pub level: Option<u32>,
+}
+
+/// Char offsets into a snippet - end is exclusive
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct Highlight { pub start: u32, pub end: u32 }
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct SearchHit {
+  pub file_path: String,
+  pub file_order: u32,
+  pub type_is: Option<NodeType>,
+  pub snippet: String,
+  pub highlights: Vec<Highlight>,
+  pub score: u32, //< Higher is a better match
+}
+
+/// Search the nodes of a single file - hits are sorted best first
+pub fn search_nodes(file_path: &str, nodes: &Nodes, query: &SearchQuery) -> Vec<SearchHit> {
+  let terms: Vec<Vec<char>> = query.text.as_deref().unwrap_or("")
+    .split_whitespace()
+    .map(lowercase_chars)
+    .collect();
+  let mut hits: Vec<SearchHit> = nodes.nodes.iter()
+    .filter(|node| passes_filters(nodes, node, query))
+    .filter_map(|node| {
+      let (score, matched_idxs) = match_terms(&terms, &lowercase_chars(&node.text))?;
+      let (snippet, highlights) = make_snippet(&node.text, &matched_idxs);
+      Some(SearchHit{file_path: file_path.to_string(), file_order: node.file_order, type_is: node.type_is.clone(), snippet, highlights, score})
+    })
+    .collect();
+  hits.sort_by(|a, b| b.score.cmp(&a.score).then(a.file_order.cmp(&b.file_order)));
+  return hits;
+}
+
+/// Search every parsed file in a workspace - hits are sorted best first
+pub fn search_workspace(workspace: &Workspace, query: &SearchQuery) -> Vec<SearchHit> {
+  let mut hits: Vec<SearchHit> = workspace.files.iter()
+    .filter_map(|file| Some((file.path.to_string_lossy().to_string(), file.nodes.as_ref().ok()?)))
+    .flat_map(|(file_path, nodes)| search_nodes(&file_path, nodes, query))
+    .collect();
+  hits.sort_by(|a, b| b.score.cmp(&a.score)
+    .then_with(|| a.file_path.cmp(&b.file_path))
+    .then(a.file_order.cmp(&b.file_order)));
+  return hits;
+}
+
+fn passes_filters(nodes: &Nodes, node: &Node, query: &SearchQuery) -> bool {
+  if !query.node_types.is_empty() && !node.type_is.as_ref().is_some_and(|type_is| query.node_types.contains(type_is)) {
+    return false;
+  }
+  if query.level.is_some_and(|level| level != node.level) { return false; }
+  if let Some(tag) = &query.tag {
+    if !tags(&node.text).iter().any(|node_tag| node_tag.eq_ignore_ascii_case(tag)) { return false; }
+  }
+  if let Some(owner) = &query.owner {
+    if !owners(&node.text).iter().any(|node_owner| node_owner.eq_ignore_ascii_case(owner)) { return false; }
+  }
+  if let Some(status) = query.status {
+    let Some(decision) = nodes.enclosing_decision(node.file_order) else { return false; };
+    if DecisionStatus::from_text(&decision.text) != status { return false; }
+  }
+  return true;
+}
+
+// Lower case char by char so indexes still line up with the original text
+fn lowercase_chars(text: &str) -> Vec<char> {
+  return text.chars().map(|c| c.to_lowercase().next().unwrap_or(c)).collect();
+}
+
+/// Every term must match - returns the summed score & all matched char indexes
+fn match_terms(terms: &Vec<Vec<char>>, text: &[char]) -> Option<(u32, Vec<usize>)> {
+  let mut total_score = 1; //< So filter only queries still count as a hit
+  let mut matched_idxs = Vec::new();
+  for term in terms {
+    let (score, idxs) = match_term(term, text)?;
+    total_score += score;
+    matched_idxs.extend(idxs);
+  }
+  matched_idxs.sort_unstable();
+  matched_idxs.dedup();
+  return Some((total_score, matched_idxs));
+}
+
+/// Exact (case-insensitive) matches score highest, then compact in order matches of the term's characters
+fn match_term(term: &[char], text: &[char]) -> Option<(u32, Vec<usize>)> {
+  if term.is_empty() || term.len() > text.len() { return None; }
+  let is_word_start = |idx: usize| idx == 0 || !text[idx - 1].is_alphanumeric();
+  let term_len = term.len() as u32;
+
+  if let Some(start) = text.windows(term.len()).position(|window| window == term) {
+    let word_bonus = if is_word_start(start) { term_len } else { 0 };
+    return Some((term_len * 4 + word_bonus, (start..start + term.len()).collect()));
+  }
+
+  let mut best: Option<(u32, Vec<usize>)> = None;
+  for start in (0..text.len()).filter(|&idx| text[idx] == term[0]) {
+    let mut idxs = vec![start];
+    for c in &term[1..] {
+      let prev = *idxs.last().unwrap();
+      match text[prev + 1..].iter().position(|text_c| text_c == c) {
+        Some(offset) => idxs.push(prev + 1 + offset),
+        None => break,
+      }
+    }
+    if idxs.len() != term.len() { break; } //< Later starts can't match either
+    let spread = idxs.last().unwrap() - start + 1;
+    if spread > term.len() * MAX_FUZZY_SPREAD { continue; }
+    let gaps = (spread - term.len()) as u32;
+    let word_bonus = if is_word_start(start) { term_len } else { 0 };
+    let score = (term_len * 2 + word_bonus).saturating_sub(gaps);
+    if best.as_ref().is_none_or(|(best_score, _)| score > *best_score) {
+      best = Some((score, idxs));
+    }
+  }
+  return best;
+}
+
+/// Cut long text down around the first match & turn matched indexes into highlight ranges
+fn make_snippet(text: &str, matched_idxs: &[usize]) -> (String, Vec<Highlight>) {
+  let chars: Vec<char> = text.chars().collect();
+  let (start, end) = if chars.len() <= SNIPPET_LEN {
+    (0, chars.len())
+  } else {
+    let start = matched_idxs.first().map_or(0, |first| first.saturating_sub(SNIPPET_LEAD)).min(chars.len() - SNIPPET_LEN);
+    (start, start + SNIPPET_LEN)
+  };
+  let mut snippet = String::new();
+  let prefix_len = if start > 0 { snippet.push('…'); 1 } else { 0 };
+  snippet.extend(&chars[start..end]);
+  if end < chars.len() { snippet.push('…'); }
+
+  let mut highlights: Vec<Highlight> = Vec::new();
+  for idx in matched_idxs.iter().filter(|&&idx| idx >= start && idx < end) {
+    let snippet_idx = (idx - start + prefix_len) as u32;
+    match highlights.last_mut() {
+      Some(last) if last.end == snippet_idx => last.end += 1,
+      _ => highlights.push(Highlight{start: snippet_idx, end: snippet_idx + 1}),
+    }
+  }
+  return (snippet, highlights);
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::file_parse::{DATA_DIR, parse_file};
+
+  #[test]
+  fn test_text_search() {
+    let nodes = parse_file(DATA_DIR.join("03_basic_encoding.md")).unwrap();
+    let query = SearchQuery{text: Some("help".to_string()), ..Default::default()};
+    let hits = search_nodes("03", &nodes, &query);
+    assert_eq!(hits.len(), 2);
+    assert_eq!(hits[0].snippet, "Would help now");
+    assert_eq!(hits[0].highlights, vec![Highlight{start: 6, end: 10}]);
+
+    // Fuzzy - `impl` is spread out in "Takes longer to implement" but still in order
+    let query = SearchQuery{text: Some("implmnt".to_string()), ..Default::default()};
+    let hits = search_nodes("03", &nodes, &query);
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].snippet, "Takes longer to implement");
+  }
+
+  #[test]
+  fn test_filters() {
+    let nodes = parse_file(DATA_DIR.join("03_basic_encoding.md")).unwrap();
+    let query = SearchQuery{node_types: vec![NodeType::Con], ..Default::default()};
+    let hits = search_nodes("03", &nodes, &query);
+    assert_eq!(hits.len(), 3);
+    assert!(hits.iter().all(|hit| hit.type_is == Some(NodeType::Con)));
+
+    let query = SearchQuery{node_types: vec![NodeType::Con], text: Some("interface".to_string()), ..Default::default()};
+    assert_eq!(search_nodes("03", &nodes, &query).len(), 1);
+
+    let query = SearchQuery{level: Some(0), status: Some(DecisionStatus::Open), ..Default::default()};
+    assert_eq!(search_nodes("03", &nodes, &query).len(), 2);
+    let query = SearchQuery{status: Some(DecisionStatus::Decided), ..Default::default()};
+    assert!(search_nodes("03", &nodes, &query).is_empty());
+  }
+
+  #[test]
+  fn test_long_snippet() {
+    let text = format!("{} latency {}", "a".repeat(100), "b".repeat(100));
+    let (snippet, highlights) = make_snippet(&text, &(101..108).collect::<Vec<usize>>());
+    assert!(snippet.starts_with('…') && snippet.ends_with('…'));
+    assert_eq!(snippet.chars().count(), SNIPPET_LEN + 2);
+    let highlighted: String = snippet.chars().skip(highlights[0].start as usize).take(7).collect();
+    assert_eq!(highlighted, "latency");
+  }
+
+  #[test]
+  fn test_search_workspace() {
+    let workspace = Workspace::open(DATA_DIR.clone()).unwrap();
+    let query = SearchQuery{text: Some("child1.2.1".to_string()), ..Default::default()};
+    let hits = search_workspace(&workspace, &query);
+    // 01, 02 and 04 all have Child1.2.1 & Child1.2.1.1
+    assert_eq!(hits.len(), 6);
+    assert!(hits.iter().any(|hit| hit.file_path.ends_with("02_long_bullets.md")));
+  }
+}
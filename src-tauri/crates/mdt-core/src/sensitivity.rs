@@ -0,0 +1,275 @@
+use crate::analysis::{Model, PROBABILITY_TOLERANCE};
+use crate::scoring::{ScoreModel, DEFAULT_WEIGHT};
+use crate::structs::{Nodes, NodeType};
+use crate::validate::Severity;
+use serde::{Serialize, Deserialize};
+
+/// Samples taken over a parameter's range before narrowing down a flip
+const STEPS: u32 = 100;
+/// Halvings of the sample step a flip is found between
+const BISECTIONS: u32 = 40;
+
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub enum ParameterKind {
+  Weight, //< A Pro / Con's `weight=` - varied from 0 to twice its value
+  Probability, //< An Outcome's `p=` - varied from 0 to 1, its sibling Outcomes rescaled to still add up to 1
+}
+
+impl ParameterKind {
+  pub fn name(&self) -> &'static str {
+    return match self {
+      ParameterKind::Weight => "weight",
+      ParameterKind::Probability => "probability",
+    };
+  }
+}
+
+/// One parameter varied over its range, with everything else as written - a bar of the tornado chart
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct ParameterSensitivity {
+  pub file_order: u32,
+  pub kind: ParameterKind,
+  pub value: f64, //< As written
+  pub low: f64,
+  pub high: f64,
+  pub decision_value_at_low: f64, //< Best Option's score / expected value
+  pub decision_value_at_high: f64,
+  pub threshold: Option<f64>, //< Value nearest the written one where the recommended Option changes - None if it holds
+  pub flips_to: Option<u32>, //< Option recommended past the threshold
+}
+
+impl ParameterSensitivity {
+  /// Length of the tornado bar
+  pub fn swing(&self) -> f64 {
+    return (self.decision_value_at_high - self.decision_value_at_low).abs();
+  }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct SensitivityReport {
+  pub decision: u32,
+  pub recommended: Option<u32>,
+  pub decision_value: f64,
+  pub parameters: Vec<ParameterSensitivity>, //< Widest swing first, then in file order - the tornado chart's order
+}
+
+/// How a Decision's Options are compared - expected values where there's analysis data, else Pro / Con weights
+enum Valuation<'a> {
+  Weights(ScoreModel<'a>),
+  ExpectedValues(Model<'a>),
+}
+
+impl Valuation<'_> {
+  /// Recommended Option & the Decision's value with one parameter changed
+  fn evaluate(&mut self, decision: u32, parameter: u32, value: f64) -> (Option<u32>, f64) {
+    match self {
+      Valuation::Weights(model) => {
+        let written = std::mem::replace(&mut model.weights[parameter as usize], value);
+        let best = model.best_option(decision);
+        model.weights[parameter as usize] = written;
+        return (best.map(|(option, _)| option), best.map_or(0.0, |(_, score)| score));
+      },
+      Valuation::ExpectedValues(model) => {
+        let written = model.probabilities.clone();
+        let siblings = outcome_siblings(model, parameter);
+        let rest = 1.0 - written[parameter as usize].unwrap_or(0.0);
+        for sibling in &siblings {
+          let share = if rest > PROBABILITY_TOLERANCE { written[*sibling as usize].unwrap_or(0.0) / rest } else { 1.0 / siblings.len() as f64 };
+          model.probabilities[*sibling as usize] = Some(share * (1.0 - value));
+        }
+        model.probabilities[parameter as usize] = Some(value);
+        let evaluated = (model.best_option(decision), model.expected_value(decision));
+        model.probabilities = written;
+        return evaluated;
+      },
+    }
+  }
+
+  /// Nearest value from `from` towards `to` where the recommended Option isn't `recommended` any more
+  fn find_flip(&mut self, decision: u32, parameter: u32, (from, to): (f64, f64), span: f64, recommended: Option<u32>) -> Option<(f64, Option<u32>)> {
+    let steps = ((to - from).abs() / span * STEPS as f64).ceil() as u32;
+    let mut same = from;
+    for step in 1..=steps {
+      let sample = from + (to - from) * step as f64 / steps as f64;
+      if self.evaluate(decision, parameter, sample).0 == recommended { same = sample; continue; }
+      let mut flipped = sample;
+      for _ in 0..BISECTIONS {
+        let middle = (same + flipped) / 2.0;
+        if self.evaluate(decision, parameter, middle).0 == recommended { same = middle; } else { flipped = middle; }
+      }
+      return Some((round((same + flipped) / 2.0), self.evaluate(decision, parameter, flipped).0));
+    }
+    return None;
+  }
+
+  fn parameter(&mut self, decision: u32, file_order: u32, kind: ParameterKind, recommended: Option<u32>) -> ParameterSensitivity {
+    let (value, low, high) = match self {
+      Valuation::Weights(model) => {
+        let weight = model.weights[file_order as usize];
+        (weight, 0.0, (2.0 * weight).max(DEFAULT_WEIGHT))
+      },
+      Valuation::ExpectedValues(model) => (model.probabilities[file_order as usize].unwrap_or(0.0), 0.0, 1.0),
+    };
+    let span = high - low;
+    let below = self.find_flip(decision, file_order, (value, low), span, recommended);
+    let above = self.find_flip(decision, file_order, (value, high), span, recommended);
+    let nearest = match (below, above) {
+      (Some(below), Some(above)) => Some(if value - below.0 <= above.0 - value { below } else { above }),
+      (below, above) => below.or(above),
+    };
+    return ParameterSensitivity{
+      file_order,
+      kind,
+      value,
+      low,
+      high,
+      decision_value_at_low: self.evaluate(decision, file_order, low).1,
+      decision_value_at_high: self.evaluate(decision, file_order, high).1,
+      threshold: nearest.map(|(threshold, _)| threshold),
+      flips_to: nearest.and_then(|(_, option)| option),
+    };
+  }
+
+  fn report(&mut self, decision: u32, parameters: Vec<(u32, ParameterKind)>) -> SensitivityReport {
+    let (recommended, decision_value) = match self {
+      Valuation::Weights(model) => model.best_option(decision).map_or((None, 0.0), |(option, score)| (Some(option), score)),
+      Valuation::ExpectedValues(model) => (model.best_option(decision), model.expected_value(decision)),
+    };
+    let mut parameters: Vec<ParameterSensitivity> = parameters.into_iter()
+      .map(|(file_order, kind)| self.parameter(decision, file_order, kind, recommended))
+      .collect();
+    parameters.sort_by(|a, b| b.swing().total_cmp(&a.swing()).then(a.file_order.cmp(&b.file_order)));
+    return SensitivityReport{decision, recommended, decision_value, parameters};
+  }
+}
+
+/// Other Outcomes of the same Chance node
+fn outcome_siblings(model: &Model, outcome: u32) -> Vec<u32> {
+  let Some(chance) = model.graph.nodes.nodes[outcome as usize].parent_idxs.first() else { return Vec::new(); };
+  return model.graph.children_of_type(*chance, NodeType::Outcome).into_iter().filter(|sibling| *sibling != outcome).collect();
+}
+
+/// Thresholds are found by bisection - round off the noise
+fn round(value: f64) -> f64 {
+  return (value * 1e6).round() / 1e6;
+}
+
+/// How robust each Decision's recommended Option is to its weights & probabilities. Decisions with analysis data vary
+/// the probabilities of Outcomes under them, others the weights of their Options' Pros / Cons - Decisions with fewer
+/// than 2 Options are skipped. Errors if any Chance node's probabilities are off
+pub fn sensitivity(nodes: &Nodes) -> Result<Vec<SensitivityReport>, String> {
+  let model = Model::new(nodes);
+  let errors: Vec<String> = model.check().into_iter().filter(|diagnostic| diagnostic.severity == Severity::Error)
+    .map(|diagnostic| diagnostic.message)
+    .collect();
+  if !errors.is_empty() { return Err(errors.join("\n")); }
+
+  let mut reports = Vec::new();
+  let mut analyzed = Vec::new();
+  for decision in model.analyzed_decisions() {
+    let values = model.analyze_decision(decision).values;
+    analyzed.extend(values.iter().map(|value| value.file_order));
+    let parameters = values.iter()
+      .filter(|value| nodes.nodes[value.file_order as usize].type_is == Some(NodeType::Outcome))
+      .filter(|value| !outcome_siblings(&model, value.file_order).is_empty())
+      .map(|value| (value.file_order, ParameterKind::Probability))
+      .collect();
+    reports.push((decision, parameters, true));
+  }
+
+  let scores = ScoreModel::new(nodes);
+  for decision in nodes.nodes.iter().filter(|node| node.type_is == Some(NodeType::Decision) && !analyzed.contains(&node.file_order)) {
+    let options = scores.graph.children_of_type(decision.file_order, NodeType::Option);
+    if options.len() < 2 { continue; }
+    let mut parameters: Vec<(u32, ParameterKind)> = options.iter()
+      .flat_map(|option| scores.graph.children_of_type(*option, NodeType::Pro).into_iter().chain(scores.graph.children_of_type(*option, NodeType::Con)))
+      .map(|file_order| (file_order, ParameterKind::Weight))
+      .collect();
+    parameters.sort_unstable_by_key(|(file_order, _)| *file_order);
+    parameters.dedup(); //< Comparative Pros / Cons count for several Options
+    reports.push((decision.file_order, parameters, false));
+  }
+  reports.sort_by_key(|(decision, _, _)| *decision);
+
+  let mut expected_values = Valuation::ExpectedValues(model);
+  let mut weights = Valuation::Weights(scores);
+  return Ok(reports.into_iter()
+    .filter(|(_, parameters, _)| !parameters.is_empty())
+    .map(|(decision, parameters, is_analyzed)| if is_analyzed { &mut expected_values } else { &mut weights }.report(decision, parameters))
+    .collect());
+}
+
+/// Quote a CSV field if it needs it
+fn csv_field(field: &str) -> String {
+  if !field.contains([',', '"', '\n', '\r']) { return field.to_string(); }
+  return format!("\"{}\"", field.replace('"', "\"\""));
+}
+
+/// Tornado chart data as CSV - a row per parameter, with node texts in place of file orders
+pub fn to_csv(nodes: &Nodes, reports: &[SensitivityReport]) -> String {
+  let text_of = |file_order: u32| csv_field(&nodes.nodes[file_order as usize].text);
+  let mut csv = String::from("decision,parameter,file_order,kind,value,low,high,decision_value_at_low,decision_value_at_high,threshold,flips_to\n");
+  for report in reports {
+    for parameter in &report.parameters {
+      csv.push_str(&format!("{},{},{},{},{},{},{},{},{},{},{}\n",
+        text_of(report.decision),
+        text_of(parameter.file_order),
+        parameter.file_order,
+        parameter.kind.name(),
+        parameter.value,
+        parameter.low,
+        parameter.high,
+        parameter.decision_value_at_low,
+        parameter.decision_value_at_high,
+        parameter.threshold.map(|threshold| threshold.to_string()).unwrap_or_default(),
+        parameter.flips_to.map(text_of).unwrap_or_default(),
+      ));
+    }
+  }
+  return csv;
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::file_parse::{DATA_DIR, parse_file, parse_str};
+
+  fn close(a: f64, b: f64) -> bool {
+    return (a - b).abs() < 1e-6;
+  }
+
+  #[test]
+  fn test_probabilities() {
+    let nodes = parse_file(DATA_DIR.join("08_decision_analysis.md")).unwrap();
+    let reports = sensitivity(&nodes).unwrap();
+    assert_eq!(reports.len(), 1); //< The later Decision is part of the analyzed one
+    let report = &reports[0];
+    assert_eq!((report.decision, report.recommended), (0, Some(1)));
+    let found: Vec<(u32, Option<f64>, Option<u32>)> = report.parameters.iter()
+      .map(|parameter| (parameter.file_order, parameter.threshold, parameter.flips_to))
+      .collect();
+    // Build: -30 + 180p beats Buy's 60 from p=0.5 / Buy: 30 + 60p when the vendor improves beats Build's 78 from p=0.8
+    assert_eq!(found, vec![(3, Some(0.5), Some(5)), (4, Some(0.5), Some(5)), (7, Some(0.8), Some(5)), (11, Some(0.2), Some(5))]);
+    let widely_used = &report.parameters[0];
+    assert!(close(widely_used.decision_value_at_low, 60.0) && close(widely_used.decision_value_at_high, 150.0), "{:?}", widely_used);
+  }
+
+  #[test]
+  fn test_weights() {
+    let nodes = parse_str("# DB (md-decision-trees)\n\nD: Which database?\n* O: Postgres\n  * P: Mature weight=3\n  * C: Ops burden\n\
+      * O: SQLite\n  * P: Simple weight=1.5").unwrap();
+    let reports = sensitivity(&nodes).unwrap();
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].recommended, Some(1));
+    let found: Vec<(u32, f64, Option<f64>, Option<u32>)> = reports[0].parameters.iter()
+      .map(|parameter| (parameter.file_order, parameter.high, parameter.threshold, parameter.flips_to))
+      .collect();
+    assert_eq!(found, vec![(2, 6.0, Some(2.5), Some(4)), (3, 2.0, Some(1.5), Some(4)), (5, 3.0, Some(2.0), Some(4))]);
+
+    let csv = to_csv(&nodes, &reports);
+    assert_eq!(csv.lines().nth(1), Some("Which database?,Mature weight=3,2,weight,3,0,6,1.5,5,2.5,SQLite"));
+  }
+}
@@ -0,0 +1,167 @@
+use crate::config::Config;
+use crate::parsers::bullet_file_parser::{BulletFileParser, START_NODE_BEGIN_REGEX};
+use crate::diff::match_nodes;
+use crate::file_write::{is_explicit_parent, ImplicitParents};
+use crate::structs::Nodes;
+
+/// A comparative index in a node's type token - ex. the `4` in `P,1,4`
+#[derive(Clone, PartialEq, Debug)]
+pub struct IndexSpan {
+  pub idx: u32,
+  pub start: u32,
+  pub end: u32,
+}
+
+/// Where a node is in the file's text - columns are UTF-16 code units, like editors / LSP positions use
+#[derive(Clone, PartialEq, Debug)]
+pub struct NodeSpan {
+  pub line: u32,
+  pub type_start: u32, //< Start of the type token (ex. `P,1,4-C,2`) - or of the text if there is none
+  pub text_start: u32, //< After the type token's `: `
+  pub line_end: u32,
+  pub indexes: Vec<IndexSpan>,
+}
+
+fn utf16_col(line: &str, byte_idx: usize) -> u32 {
+  return line[..byte_idx].encode_utf16().count() as u32;
+}
+
+/// One span per node in file order - the parser makes a node of each non-empty line after the title
+pub fn node_spans(content: &str, config: &Config) -> Vec<NodeSpan> {
+  let comparative_regex = config.comparative_regex();
+  let mut spans = Vec::new();
+  for (line_idx, line) in content.lines().enumerate().skip(1) {
+    if line.is_empty() { continue; }
+    // Same bullet handling as BulletFileParser::handle_line
+    let type_start = match (START_NODE_BEGIN_REGEX.find(line), line.find('*')) {
+      (Some(_), Some(bullet_idx)) => (bullet_idx + 2).min(line.len()),
+      _ => 0,
+    };
+    let rest = &line[type_start..];
+    let mut text_start = type_start;
+    let mut indexes = Vec::new();
+    if let Some(colon_idx) = rest.find(':') {
+      let type_str = &rest[..colon_idx];
+      if let Some(caps) = comparative_regex.captures(type_str) {
+        for group in [caps.get(2), caps.get(4)].into_iter().flatten() {
+          let mut start = type_start + group.start();
+          for idx_str in group.as_str().split(',') {
+            if let Ok(idx) = idx_str.parse() {
+              indexes.push(IndexSpan{idx, start: utf16_col(line, start), end: utf16_col(line, start + idx_str.len())});
+            }
+            start += idx_str.len() + 1;
+          }
+        }
+      }
+      if !indexes.is_empty() || config.node_type(type_str).is_some() {
+        text_start = type_start + colon_idx + 1;
+        if rest[colon_idx + 1..].starts_with(' ') { text_start += 1; }
+      }
+    }
+    spans.push(NodeSpan{
+      line: line_idx as u32,
+      type_start: utf16_col(line, type_start),
+      text_start: utf16_col(line, text_start),
+      line_end: utf16_col(line, line.len()),
+      indexes,
+    });
+  }
+  return spans;
+}
+
+/// Line of the first parse error - parse errors aren't tied to a node, so this finds where to show them
+pub fn parse_error_line(content: &str, config: &Config) -> Option<u32> {
+  let mut parser = BulletFileParser::with_config(config);
+  for (line_idx, line) in content.lines().enumerate().skip(1) {
+    if parser.handle_line(line).is_err() { return Some(line_idx as u32); }
+  }
+  return None;
+}
+
+/// Point comparative indexes back at the nodes they pointed at in `saved` - ex. after lines were inserted above them
+/// - only nodes whose indexes weren't edited since are changed - None if nothing needs to change
+pub fn renumber_links(saved: &Nodes, nodes: &Nodes) -> Option<Nodes> {
+  let saved_to_new = match_nodes(saved, nodes);
+  let map_idxs = |idxs: &Vec<u32>| -> Vec<u32> {
+    idxs.iter().filter_map(|idx| saved_to_new.get(*idx as usize).copied().flatten()).collect()
+  };
+  let mut renumbered = nodes.clone();
+  let mut changed = false;
+  let mut implicit_parents = ImplicitParents::default();
+  for (saved_node, new_idx) in saved.nodes.iter().zip(saved_to_new.iter()) {
+    let implicit_parent = implicit_parents.next(saved_node);
+    let Some(new_idx) = new_idx else { continue; };
+    let node = &mut renumbered.nodes[*new_idx as usize];
+    // Same condition the writer uses to write the indexes out
+    let is_explicit = saved_node.parent_idxs.len() > 1 || !saved_node.parent_idxs_diff_type.is_empty()
+      || saved_node.type_is.as_ref().is_some_and(|found_type| is_explicit_parent(found_type, saved_node, implicit_parent));
+    if !is_explicit || node.parent_idxs != saved_node.parent_idxs || node.parent_idxs_diff_type != saved_node.parent_idxs_diff_type {
+      continue;
+    }
+    let parent_idxs = map_idxs(&saved_node.parent_idxs);
+    let parent_idxs_diff_type = map_idxs(&saved_node.parent_idxs_diff_type);
+    if parent_idxs.is_empty() { continue; } //< Everything it pointed at is gone - left for validation to report
+    if parent_idxs != node.parent_idxs || parent_idxs_diff_type != node.parent_idxs_diff_type {
+      node.parent_idxs = parent_idxs;
+      node.parent_idxs_diff_type = parent_idxs_diff_type;
+      changed = true;
+    }
+  }
+  return changed.then_some(renumbered);
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::file_parse::parse_str;
+
+  const CONTENT: &str = "# Test (md-decision-trees)
+
+D: Which database?
+* O: Postgres
+  * P: Mature
+* O: SQLite
+  * P,3-C,1: No server to run";
+
+  #[test]
+  fn test_node_spans() {
+    let spans = node_spans(CONTENT, &Config::default());
+    assert_eq!(spans.len(), 5);
+    assert_eq!(spans[0], NodeSpan{line: 2, type_start: 0, text_start: 3, line_end: 18, indexes: vec![]});
+    assert_eq!(spans[2].line, 4);
+    assert_eq!(spans[2].text_start, 7);
+    assert_eq!(spans[4].indexes, vec![IndexSpan{idx: 3, start: 6, end: 7}, IndexSpan{idx: 1, start: 10, end: 11}]);
+  }
+
+  #[test]
+  fn test_parse_error_line() {
+    assert_eq!(parse_error_line(CONTENT, &Config::default()), None);
+    assert_eq!(parse_error_line(&format!("{}\n* Missing a type", CONTENT), &Config::default()), Some(7));
+  }
+
+  #[test]
+  fn test_renumber_links() {
+    let saved = parse_str(CONTENT).unwrap();
+    assert_eq!(renumber_links(&saved, &saved), None);
+    // A line inserted above shifts the Options, but P,3-C,1 wasn't updated
+    let inserted = parse_str(&CONTENT.replace("D: Which database?", "D: Which database?\n* N: Decide by Friday")).unwrap();
+    let renumbered = renumber_links(&saved, &inserted).unwrap();
+    assert_eq!(renumbered.nodes[5].parent_idxs, vec![4]);
+    assert_eq!(renumbered.nodes[5].parent_idxs_diff_type, vec![2]);
+    // Indexes the user already changed are left alone
+    let edited = parse_str(&CONTENT.replace("D: Which database?", "D: Which database?\n* N: Decide by Friday").replace("P,3-C,1", "P,4-C,2")).unwrap();
+    assert_eq!(renumber_links(&saved, &edited), None);
+  }
+
+  #[test]
+  fn test_renumber_single_explicit_parent() {
+    let content = CONTENT.replace("P,3-C,1: No server to run", "P,1: Same SQL dialect");
+    let saved = parse_str(&content).unwrap();
+    assert_eq!(saved.nodes[4].parent_idxs, vec![1]);
+    let inserted = parse_str(&content.replace("D: Which database?", "D: Which database?\n* N: Decide by Friday")).unwrap();
+    let renumbered = renumber_links(&saved, &inserted).unwrap();
+    assert_eq!(renumbered.nodes[5].parent_idxs, vec![2]);
+    // Pros under their own Option have nothing written out to renumber
+    assert_eq!(renumbered.nodes[3].parent_idxs, inserted.nodes[3].parent_idxs);
+  }
+}
@@ -0,0 +1,96 @@
+use serde::{Serialize, Deserialize};
+
+use std::str::FromStr;
+
+// TODO - may be able to leverage complex enums to do a combo Pro Con tracing to multiple nodes?
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub enum NodeType { 
+  Decision, Option,
+  Pro, Con,
+  Note,
+  Assumption, Risk, //< Attached to a Decision / Option - see node_meta for their status & rating tags
+  Chance, Outcome, //< Uncertainty after an Option - each Outcome has a `p=` & optional `value=`, see analysis.rs
+  Custom(String), //< Id of a type declared in the project's `.mdt.toml` - ex. `question` for `[custom_types.question]`
+}
+
+impl NodeType {
+  /// Lowercase name used by configs, queries & the UI's css classes - a custom type's id as is
+  pub fn id(&self) -> &str {
+    return match self {
+      NodeType::Decision => "decision", NodeType::Option => "option",
+      NodeType::Pro => "pro", NodeType::Con => "con",
+      NodeType::Note => "note",
+      NodeType::Assumption => "assumption", NodeType::Risk => "risk",
+      NodeType::Chance => "chance", NodeType::Outcome => "outcome",
+      NodeType::Custom(id) => id,
+    };
+  }
+}
+
+impl FromStr for NodeType {
+  type Err = (); //< required for FromStr
+  fn from_str(input: &str) -> Result<NodeType, Self::Err> {
+    match input {
+        "D" => Ok(NodeType::Decision), "O" => Ok(NodeType::Option),
+        "P" => Ok(NodeType::Pro), "C" => Ok(NodeType::Con),
+        "N" => Ok(NodeType::Note),
+        "A" => Ok(NodeType::Assumption), "R" => Ok(NodeType::Risk),
+        "X" => Ok(NodeType::Chance), "Y" => Ok(NodeType::Outcome),
+        _ => Err(()),
+    }
+  }
+}
+
+#[derive(Default, Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct Node {
+  pub text: String,
+  pub type_is: Option<NodeType>, //< `type` is reserved, so type_is should read well...
+  // Note - can't use usize here since BigInt is forbidden for bindings
+  pub file_order: u32, //< To ensure re-written file is in original order
+  pub level: u32, //< Level to know how many spaces to re-write since left stripped before serializing
+  pub parent_idxs: Vec<u32>,
+  pub parent_idxs_diff_type: Vec<u32>, //< If type_is Pro/Con, but this node is also a Con/Pro for other nodes, hold those indexes here
+}
+
+// TODO - less boiler plate way to do this in rust?
+impl Node {
+  pub fn new(
+    text: String,
+    type_is: NodeType,
+    file_order: u32,
+    level: u32,
+    parent_idxs: Vec<u32>,
+    parent_idxs_diff_type: Vec<u32>,
+  ) -> Self {
+    Node {
+      text,
+      type_is: Some(type_is),
+      file_order,
+      level,
+      parent_idxs,
+      parent_idxs_diff_type,
+    }
+  }
+}
+
+#[derive(Default, Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct Nodes {
+  pub title: String, //< Markdowns top title - used to re-write later
+  pub nodes: Vec<Node>
+}
+
+impl Nodes {
+  /// Closest Decision above a node (or the node itself if its a Decision) - follows the first parent of each node
+  pub fn enclosing_decision(&self, file_order: u32) -> Option<&Node> {
+    let mut curr = self.nodes.get(file_order as usize)?;
+    // Bounded in case a bad comparative index creates a cycle
+    for _ in 0..self.nodes.len() {
+      if curr.type_is == Some(NodeType::Decision) { return Some(curr); }
+      curr = self.nodes.get(*curr.parent_idxs.first()? as usize)?;
+    }
+    return None;
+  }
+}
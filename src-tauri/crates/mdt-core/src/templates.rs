@@ -0,0 +1,182 @@
+use crate::config::Config;
+use crate::file_parse::{parse_str_with, REQUIRED_HEADER};
+use crate::structs::Nodes;
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs::{read_dir, read_to_string, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+// For non-const statics
+lazy_static! {
+  // Filled in when a file is made from the template - ex. `D: Which {{library}} library should we use?`
+  pub static ref PLACEHOLDER_REGEX: Regex = Regex::new(r"\{\{\s*([\w\-]+)\s*\}\}").unwrap();
+}
+
+/// Shipped with the app - a template with the same name in the user's template directory replaces one of these
+const BUILT_IN_TEMPLATES: [(&str, &str); 3] = [
+  ("hiring", include_str!("../templates/hiring.md")),
+  ("hosting", include_str!("../templates/hosting.md")),
+  ("library-choice", include_str!("../templates/library-choice.md")),
+];
+
+/// A decision file with `{{placeholders}}` - named after its file, without the `.md`
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct Template {
+  pub name: String,
+  pub path: Option<String>, //< None for built in templates
+  pub placeholders: Vec<String>, //< In the order they first appear
+  pub content: String,
+}
+
+impl Template {
+  pub fn new(name: &str, path: Option<String>, content: String) -> Self {
+    return Template{name: name.to_string(), path, placeholders: placeholders(&content), content};
+  }
+
+  pub fn load(path: &Path) -> Result<Template, Box<dyn Error>> {
+    let name = path.file_stem().ok_or("Template path has no file name")?.to_string_lossy().to_string();
+    return Ok(Template::new(&name, Some(path.to_string_lossy().to_string()), read_to_string(path)?));
+  }
+
+  /// Content with every placeholder filled in & the `(md-decision-trees)` header - a first line that is a `# title`
+  /// gets the header added, otherwise `# <title> (md-decision-trees)` is put above the template
+  pub fn instantiate(&self, values: &BTreeMap<String, String>, title: &str) -> Result<String, String> {
+    let missing: Vec<&str> = self.placeholders.iter().filter(|placeholder| !values.contains_key(*placeholder)).map(String::as_str).collect();
+    if !missing.is_empty() { return Err(format!("Template '{}' needs values for: {}", self.name, missing.join(", "))); }
+    if let Some((name, _)) = values.iter().find(|(_, value)| value.contains(['\n', '\r'])) {
+      return Err(format!("The value for '{}' has to be a single line", name));
+    }
+    let content = PLACEHOLDER_REGEX.replace_all(&self.content, |caps: &regex::Captures| values[&caps[1]].clone()).to_string();
+    let first_line = content.lines().next().unwrap_or_default();
+    if first_line.contains(REQUIRED_HEADER) { return Ok(content); }
+    if first_line.starts_with("# ") {
+      return Ok(format!("{} {}{}", first_line.trim_end(), REQUIRED_HEADER, &content[first_line.len()..]));
+    }
+    return Ok(format!("# {} {}\n\n{}", title, REQUIRED_HEADER, content));
+  }
+}
+
+/// Placeholder names in a template, each once
+pub fn placeholders(content: &str) -> Vec<String> {
+  let mut found: Vec<String> = Vec::new();
+  for caps in PLACEHOLDER_REGEX.captures_iter(content) {
+    if !found.iter().any(|name| name == &caps[1]) { found.push(caps[1].to_string()); }
+  }
+  return found;
+}
+
+/// Where the user keeps their own templates - `$MDT_TEMPLATE_DIR`, else `mdt/templates` in the user's config directory
+pub fn user_template_dir() -> Option<PathBuf> {
+  if let Some(dir) = std::env::var_os("MDT_TEMPLATE_DIR") { return Some(PathBuf::from(dir)); }
+  let config_dir = if cfg!(windows) {
+    std::env::var_os("APPDATA").map(PathBuf::from)
+  } else {
+    std::env::var_os("XDG_CONFIG_HOME").map(PathBuf::from).or(std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+  };
+  return config_dir.map(|dir| dir.join("mdt").join("templates"));
+}
+
+/// Built in templates & the `.md` files in the user's template directory, by name - a missing directory is fine
+pub fn templates(user_dir: Option<&Path>) -> Result<Vec<Template>, Box<dyn Error>> {
+  let mut found: BTreeMap<String, Template> = BUILT_IN_TEMPLATES.iter()
+    .map(|(name, content)| (name.to_string(), Template::new(name, None, content.to_string())))
+    .collect();
+  if let Some(user_dir) = user_dir {
+    let entries = match read_dir(user_dir) {
+      Ok(entries) => entries,
+      Err(err) if err.kind() == ErrorKind::NotFound => return Ok(found.into_values().collect()),
+      Err(err) => return Err(err.into()),
+    };
+    for entry in entries {
+      let path = entry?.path();
+      if !path.is_file() || path.extension().is_none_or(|extension| extension != "md") { continue; }
+      let template = Template::load(&path)?;
+      found.insert(template.name.clone(), template);
+    }
+  }
+  return Ok(found.into_values().collect());
+}
+
+/// Built in or user template by name - see `Template::load` for a template file anywhere else
+pub fn find_template(name: &str, user_dir: Option<&Path>) -> Result<Template, Box<dyn Error>> {
+  return templates(user_dir)?.into_iter().find(|template| template.name == name)
+    .ok_or(format!("No template named '{}'", name).into());
+}
+
+/// Make a new decision file from a template - refuses to replace an existing file. The filled in template has to parse
+/// in the format of the new file's `.mdt.toml`
+pub fn new_from_template(template: &Template, values: &BTreeMap<String, String>, file_path: &Path) -> Result<Nodes, Box<dyn Error>> {
+  let title = file_path.file_stem().map_or(template.name.clone(), |stem| stem.to_string_lossy().to_string());
+  let content = template.instantiate(values, &title)?;
+  let nodes = parse_str_with(&content, &Config::discover(file_path)?)
+    .map_err(|err| format!("Template '{}' doesn't make a valid decision file: {}", template.name, err))?;
+  // Only created if it doesn't exist yet - so a file made while the template was being filled in is never replaced
+  let mut file = match OpenOptions::new().write(true).create_new(true).open(file_path) {
+    Ok(file) => file,
+    Err(err) if err.kind() == ErrorKind::AlreadyExists => Err(format!("{} already exists", file_path.display()))?,
+    Err(err) => return Err(err.into()),
+  };
+  file.write_all(content.as_bytes())?;
+  return Ok(nodes);
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::validate::validate;
+  use crate::test_utils::scratch_dir;
+  use std::fs::{create_dir_all, write};
+
+  fn values(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+    return pairs.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect();
+  }
+
+  #[test]
+  fn test_instantiate() {
+    let template = Template::new("db", None, "D: Which {{ kind }} database for {{project}}?\n* O: {{kind}}".to_string());
+    assert_eq!(template.placeholders, vec!["kind", "project"]);
+    assert!(template.instantiate(&values(&[("kind", "SQL")]), "db").unwrap_err().contains("project"));
+    assert!(template.instantiate(&values(&[("kind", "SQL\n* O: Injected"), ("project", "x")]), "db").is_err());
+    assert_eq!(template.instantiate(&values(&[("kind", "SQL"), ("project", "billing")]), "db").unwrap(),
+      "# db (md-decision-trees)\n\nD: Which SQL database for billing?\n* O: SQL");
+
+    let template = Template::new("db", None, "# {{project}} database\n\nD: Which?".to_string());
+    assert_eq!(template.instantiate(&values(&[("project", "Billing")]), "db").unwrap(), "# Billing database (md-decision-trees)\n\nD: Which?");
+  }
+
+  #[test]
+  fn test_built_in_templates_are_valid() {
+    for template in templates(None).unwrap() {
+      let filled: BTreeMap<String, String> = template.placeholders.iter().map(|name| (name.clone(), "x".to_string())).collect();
+      let nodes = parse_str_with(&template.instantiate(&filled, "x").unwrap(), &Config::default()).unwrap();
+      assert!(validate(&nodes).is_empty(), "{} should be valid", template.name);
+    }
+  }
+
+  #[test]
+  fn test_new_from_template() {
+    let dir = scratch_dir("templates", "new_from_template");
+    let user_dir = dir.join("templates");
+    create_dir_all(&user_dir).unwrap();
+    write(user_dir.join("hiring.md"), "D: Hire {{name}}?\n* O: Yes\n* O: No").unwrap();
+    write(user_dir.join("notes.txt"), "Not a template").unwrap();
+
+    let names: Vec<(String, bool)> = templates(Some(&user_dir)).unwrap().into_iter().map(|template| (template.name, template.path.is_some())).collect();
+    assert_eq!(names, vec![("hiring".to_string(), true), ("hosting".to_string(), false), ("library-choice".to_string(), false)]);
+    assert!(templates(Some(&dir.join("missing"))).unwrap().len() == BUILT_IN_TEMPLATES.len());
+
+    let template = find_template("hiring", Some(&user_dir)).unwrap();
+    let file_path = dir.join("ann.md");
+    let nodes = new_from_template(&template, &values(&[("name", "Ann")]), &file_path).unwrap();
+    assert_eq!(nodes.nodes[0].text, "Hire Ann?");
+    assert_eq!(read_to_string(&file_path).unwrap(), "# ann (md-decision-trees)\n\nD: Hire Ann?\n* O: Yes\n* O: No");
+    assert!(new_from_template(&template, &values(&[("name", "Bob")]), &file_path).is_err()); //< Never replaces a file
+    assert!(find_template("missing", Some(&user_dir)).is_err());
+    assert!(find_template(&user_dir.join("hiring.md").to_string_lossy(), Some(&user_dir)).is_err()); //< Only by name
+  }
+}
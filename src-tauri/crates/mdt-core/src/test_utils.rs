@@ -0,0 +1,11 @@
+use std::fs::{create_dir_all, remove_dir_all};
+use std::path::PathBuf;
+
+/// Empty directory under the OS temp dir for one test - tests that touch the file system shouldn't change test/data.
+/// Per process, so test runs at the same time don't share it
+pub fn scratch_dir(module: &str, name: &str) -> PathBuf {
+  let dir = std::env::temp_dir().join(format!("mdt_{}_tests_{}", module, std::process::id())).join(name);
+  let _ = remove_dir_all(&dir);
+  create_dir_all(&dir).unwrap();
+  return dir;
+}
@@ -0,0 +1,110 @@
+use crate::node_meta::{AssumptionStatus, RiskRating};
+use crate::structs::{Nodes, NodeType};
+use crate::workspace::Workspace;
+use serde::{Serialize, Deserialize};
+
+/// Where an Assumption / Risk is & what it's attached to
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct TrackedNode {
+  pub file_path: String,
+  pub file_order: u32,
+  pub text: String,
+  pub attached_to: Option<String>, //< Text of the Decision / Option it's under
+  pub decision: Option<String>, //< Decision to revisit if it breaks / happens
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct TrackedAssumption {
+  pub node: TrackedNode,
+  pub status: AssumptionStatus,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct TrackedRisk {
+  pub node: TrackedNode,
+  pub rating: RiskRating,
+  pub score: u32, //< Likelihood x impact - see `RiskRating::score`
+}
+
+/// Assumptions that aren't validated (broken ones first) & high risks (highest score first)
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct TrackingReport {
+  pub assumptions: Vec<TrackedAssumption>,
+  pub risks: Vec<TrackedRisk>,
+}
+
+impl TrackingReport {
+  fn sort(&mut self) {
+    self.assumptions.sort_by(|a, b| (b.status == AssumptionStatus::Broken).cmp(&(a.status == AssumptionStatus::Broken))
+      .then_with(|| a.node.file_path.cmp(&b.node.file_path))
+      .then(a.node.file_order.cmp(&b.node.file_order)));
+    self.risks.sort_by(|a, b| b.score.cmp(&a.score)
+      .then_with(|| a.node.file_path.cmp(&b.node.file_path))
+      .then(a.node.file_order.cmp(&b.node.file_order)));
+  }
+}
+
+/// Report for a single file's nodes
+pub fn track_nodes(file_path: &str, nodes: &Nodes) -> TrackingReport {
+  let mut report = TrackingReport::default();
+  for node in &nodes.nodes {
+    let tracked = || TrackedNode{
+      file_path: file_path.to_string(),
+      file_order: node.file_order,
+      text: node.text.clone(),
+      attached_to: node.parent_idxs.first().and_then(|idx| nodes.nodes.get(*idx as usize)).map(|parent| parent.text.clone()),
+      decision: nodes.enclosing_decision(node.file_order).map(|decision| decision.text.clone()),
+    };
+    match node.type_is {
+      Some(NodeType::Assumption) => {
+        let status = AssumptionStatus::from_text(&node.text);
+        if status != AssumptionStatus::Validated { report.assumptions.push(TrackedAssumption{node: tracked(), status}); }
+      },
+      Some(NodeType::Risk) => {
+        let rating = RiskRating::from_text(&node.text);
+        if rating.is_high() { report.risks.push(TrackedRisk{node: tracked(), rating, score: rating.score()}); }
+      },
+      _ => {},
+    }
+  }
+  report.sort();
+  return report;
+}
+
+/// Report across every file that parsed
+pub fn track_workspace(workspace: &Workspace) -> TrackingReport {
+  let mut report = TrackingReport::default();
+  for file in &workspace.files {
+    let Ok(nodes) = &file.nodes else { continue; };
+    let file_report = track_nodes(&file.path.to_string_lossy(), nodes);
+    report.assumptions.extend(file_report.assumptions);
+    report.risks.extend(file_report.risks);
+  }
+  report.sort();
+  return report;
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::file_parse::parse_str;
+  use crate::node_meta::RiskLevel;
+
+  #[test]
+  fn test_track_nodes() {
+    let nodes = parse_str("# DB (md-decision-trees)\n\nD: Which database?\n* A: Traffic stays low #validated\n* O: SQLite\n  \
+      * A: One writer at a time\n  * R: Corrupted on NFS #likelihood-low #impact-high\n  * A: Fits on one disk #broken\n* O: Postgres\n  \
+      * R: Ops burden #impact-high").unwrap();
+    let report = track_nodes("db.md", &nodes);
+    let assumptions: Vec<(&str, AssumptionStatus)> = report.assumptions.iter().map(|found| (found.node.text.as_str(), found.status)).collect();
+    assert_eq!(assumptions, vec![("Fits on one disk #broken", AssumptionStatus::Broken), ("One writer at a time", AssumptionStatus::Unvalidated)]);
+    assert_eq!(report.assumptions[0].node.attached_to.as_deref(), Some("SQLite"));
+    assert_eq!(report.assumptions[0].node.decision.as_deref(), Some("Which database?"));
+    assert_eq!(report.risks.len(), 1);
+    assert_eq!((report.risks[0].node.file_order, report.risks[0].rating.impact, report.risks[0].score), (7, RiskLevel::High, 6));
+  }
+}
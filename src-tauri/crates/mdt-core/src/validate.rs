@@ -0,0 +1,191 @@
+use crate::ahp;
+use crate::analysis::Model;
+use crate::config::{Config, RuleLevel};
+use crate::node_meta::malformed_due_dates;
+use crate::structs::{Nodes, NodeType};
+use serde::{Serialize, Deserialize};
+
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub enum Severity { Error, Warning }
+
+/// Problem found in a file - file_order is None when it isn't tied to a single node (ex. parse errors)
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct Diagnostic {
+  pub severity: Severity,
+  pub file_order: Option<u32>,
+  pub message: String,
+}
+
+impl Diagnostic {
+  pub fn error(file_order: Option<u32>, message: String) -> Self {
+    Diagnostic{severity: Severity::Error, file_order, message}
+  }
+  pub fn warning(file_order: Option<u32>, message: String) -> Self {
+    Diagnostic{severity: Severity::Warning, file_order, message}
+  }
+}
+
+/// Diagnostic for a configurable rule - None if the rule is off
+fn rule_diagnostic(level: RuleLevel, file_order: u32, message: String) -> Option<Diagnostic> {
+  return match level {
+    RuleLevel::Off => None,
+    RuleLevel::Warning => Some(Diagnostic::warning(Some(file_order), message)),
+    RuleLevel::Error => Some(Diagnostic::error(Some(file_order), message)),
+  };
+}
+
+/// Check the relationships between already parsed nodes - with the built in rules
+pub fn validate(nodes: &Nodes) -> Vec<Diagnostic> {
+  return validate_with(nodes, &Config::default());
+}
+
+/// Check nodes against a project's `.mdt.toml` - its rules & which node types it allows
+pub fn validate_with(nodes: &Nodes, config: &Config) -> Vec<Diagnostic> {
+  let rules = &config.validation;
+  let mut diagnostics = Vec::new();
+  let num_nodes = nodes.nodes.len();
+  for node in &nodes.nodes {
+    if let Some(node_type) = node.type_is.as_ref().filter(|node_type| !config.is_allowed(node_type)) {
+      diagnostics.push(Diagnostic::error(Some(node.file_order),
+        format!("'{}' is a {} node - this project's .mdt.toml doesn't allow them", node.text, node_type.id())));
+    }
+    if let Some(NodeType::Custom(id)) = &node.type_is {
+      let allowed_parents = config.custom_types.get(id).map_or(&[][..], |custom_type| &custom_type.parents[..]);
+      let parent_type = node.parent_idxs.first().and_then(|idx| nodes.nodes.get(*idx as usize)).and_then(|parent| parent.type_is.as_ref());
+      if !allowed_parents.is_empty() && !parent_type.is_some_and(|parent_type| allowed_parents.iter().any(|allowed| allowed == parent_type.id())) {
+        diagnostics.extend(rule_diagnostic(rules.custom_type_parent, node.file_order,
+          format!("'{}' can only be under {} nodes", node.text, allowed_parents.join(" / "))));
+      }
+    }
+    if matches!(node.type_is, Some(NodeType::Assumption) | Some(NodeType::Risk)) {
+      let parent_type = node.parent_idxs.first().and_then(|idx| nodes.nodes.get(*idx as usize)).and_then(|parent| parent.type_is.as_ref());
+      if !matches!(parent_type, Some(NodeType::Decision) | Some(NodeType::Option)) {
+        diagnostics.extend(rule_diagnostic(rules.assumption_risk_parent, node.file_order,
+          format!("'{}' should be under the Decision or Option it affects", node.text)));
+      }
+    }
+    for tag in malformed_due_dates(&node.text) {
+      diagnostics.push(Diagnostic::warning(Some(node.file_order), format!("'#{}' in '{}' isn't a #due-YYYY-MM-DD date", tag, node.text)));
+    }
+    if let Some(max_depth) = rules.max_depth.filter(|max_depth| node.level > *max_depth) {
+      diagnostics.push(Diagnostic::warning(Some(node.file_order),
+        format!("'{}' is nested {} levels deep - the limit is {}", node.text, node.level, max_depth)));
+    }
+    if node.type_is == Some(NodeType::Decision)
+      && !nodes.nodes.iter().any(|other| other.type_is == Some(NodeType::Option) && other.parent_idxs.contains(&node.file_order)) {
+      diagnostics.extend(rule_diagnostic(rules.decision_without_options, node.file_order, format!("'{}' has no Options", node.text)));
+    }
+    let is_comparative = matches!(node.type_is, Some(NodeType::Pro) | Some(NodeType::Con));
+    for idx in node.parent_idxs.iter().chain(node.parent_idxs_diff_type.iter()) {
+      let Some(parent) = nodes.nodes.get(*idx as usize) else {
+        diagnostics.push(Diagnostic::error(Some(node.file_order),
+          format!("'{}' references node {} but the file only has {} nodes", node.text, idx, num_nodes)));
+        continue;
+      };
+      // Comparative nodes should only ever point to Options
+      if is_comparative && parent.type_is.is_some() && parent.type_is != Some(NodeType::Option) {
+        diagnostics.extend(rule_diagnostic(rules.non_option_link, node.file_order,
+          format!("'{}' is linked to '{}', which is not an Option", node.text, parent.text)));
+      }
+    }
+  }
+  diagnostics.extend(Model::new(nodes).check());
+  diagnostics.extend(ahp::check(nodes));
+  return diagnostics;
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::structs::Node;
+  use crate::file_parse::{DATA_DIR, parse_file};
+
+  #[test]
+  fn test_valid_files_have_no_diagnostics() {
+    for file_name in ["01_bullets.md", "03_basic_encoding.md", "05_comparative_encoding_output.md", "06_assumptions_and_risks.md", "07_action_items.md",
+      "08_decision_analysis.md", "09_pairwise_comparisons.md"] {
+      let nodes = parse_file(DATA_DIR.join(file_name)).unwrap();
+      assert!(validate(&nodes).is_empty(), "{} should be valid", file_name);
+    }
+  }
+
+  #[test]
+  fn test_bad_links() {
+    let nodes = Nodes{title: String::new(), nodes: vec![
+      Node::new("Decision".to_string(), NodeType::Decision, 0, 0, vec![], vec![]),
+      Node::new("Option".to_string(), NodeType::Option, 1, 1, vec![0], vec![]),
+      Node::new("Pro".to_string(), NodeType::Pro, 2, 2, vec![1], vec![0]),
+      Node::new("Con".to_string(), NodeType::Con, 3, 2, vec![9], vec![]),
+    ]};
+    let diagnostics = validate(&nodes);
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0].severity, Severity::Warning);
+    assert_eq!(diagnostics[0].file_order, Some(2));
+    assert_eq!(diagnostics[1].severity, Severity::Error);
+    assert_eq!(diagnostics[1].file_order, Some(3));
+  }
+
+  #[test]
+  fn test_configured_rules() {
+    let nodes = Nodes{title: String::new(), nodes: vec![
+      Node::new("Decision".to_string(), NodeType::Decision, 0, 0, vec![], vec![]),
+      Node::new("Note".to_string(), NodeType::Note, 1, 1, vec![0], vec![]),
+      Node::new("Pro".to_string(), NodeType::Pro, 2, 2, vec![1], vec![]),
+    ]};
+    assert_eq!(validate(&nodes).len(), 1); //< Only the Pro linked to a Note
+    let config = Config::from_toml(r#"
+      node_types.note.allowed = false
+      [validation]
+      non_option_link = "off"
+      decision_without_options = "error"
+      max_depth = 1
+    "#).unwrap();
+    let diagnostics = validate_with(&nodes, &config);
+    let found: Vec<(Severity, Option<u32>)> = diagnostics.iter().map(|diagnostic| (diagnostic.severity, diagnostic.file_order)).collect();
+    assert_eq!(found, vec![(Severity::Error, Some(0)), (Severity::Error, Some(1)), (Severity::Warning, Some(2))]);
+  }
+
+  #[test]
+  fn test_custom_type_parents() {
+    let question = NodeType::Custom("question".to_string());
+    let nodes = Nodes{title: String::new(), nodes: vec![
+      Node::new("Decision".to_string(), NodeType::Decision, 0, 0, vec![], vec![]),
+      Node::new("Option".to_string(), NodeType::Option, 1, 1, vec![0], vec![]),
+      Node::new("Under an option".to_string(), question.clone(), 2, 2, vec![1], vec![]),
+      Node::new("Under a decision".to_string(), question.clone(), 3, 1, vec![0], vec![]),
+    ]};
+    let config = Config::from_toml("[custom_types.question]\ntoken = \"Q\"\nparents = [\"option\"]").unwrap();
+    let diagnostics = validate_with(&nodes, &config);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!((diagnostics[0].severity, diagnostics[0].file_order), (Severity::Warning, Some(3)));
+    let config = Config::from_toml("[custom_types.question]\ntoken = \"Q\"\nparents = [\"option\"]\n[validation]\ncustom_type_parent = \"off\"").unwrap();
+    assert!(validate_with(&nodes, &config).is_empty());
+  }
+
+  #[test]
+  fn test_malformed_due_dates() {
+    let nodes = Nodes{title: String::new(), nodes: vec![
+      Node::new("[ ] Ship it #due-friday #due-2026-11-01".to_string(), NodeType::Note, 0, 0, vec![], vec![]),
+    ]};
+    let diagnostics = validate(&nodes);
+    assert_eq!(diagnostics.len(), 1);
+    assert!(diagnostics[0].message.contains("#due-friday"), "{}", diagnostics[0].message);
+  }
+
+  #[test]
+  fn test_assumption_risk_parents() {
+    let nodes = Nodes{title: String::new(), nodes: vec![
+      Node::new("Decision".to_string(), NodeType::Decision, 0, 0, vec![], vec![]),
+      Node::new("On the decision".to_string(), NodeType::Assumption, 1, 1, vec![0], vec![]),
+      Node::new("Option".to_string(), NodeType::Option, 2, 1, vec![0], vec![]),
+      Node::new("On the option".to_string(), NodeType::Risk, 3, 2, vec![2], vec![]),
+      Node::new("Pro".to_string(), NodeType::Pro, 4, 2, vec![2], vec![]),
+      Node::new("On a pro".to_string(), NodeType::Risk, 5, 3, vec![4], vec![]),
+      Node::new("Top level".to_string(), NodeType::Assumption, 6, 0, vec![], vec![]),
+    ]};
+    let found: Vec<Option<u32>> = validate(&nodes).iter().map(|diagnostic| diagnostic.file_order).collect();
+    assert_eq!(found, vec![Some(5), Some(6)]);
+  }
+}
@@ -0,0 +1,185 @@
+use crate::config::Config;
+use crate::file_parse::{parse_str_with, REQUIRED_HEADER};
+use crate::node_meta::DecisionStatus;
+use crate::structs::{Nodes, NodeType};
+use crate::validate::{validate_with, Diagnostic};
+use serde::{Serialize, Deserialize};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::error::Error;
+use std::fs::{read_dir, read_to_string, File};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+/// A decision file found while scanning - holds the parse error instead of nodes if it couldn't be parsed
+pub struct WorkspaceFile {
+  pub path: PathBuf,
+  pub nodes: Result<Nodes, String>,
+  pub config: Config, //< From the file's `.mdt.toml` - the built in one if it couldn't be loaded
+}
+
+/// Every decision file under a root directory
+pub struct Workspace {
+  pub root: PathBuf,
+  pub files: Vec<WorkspaceFile>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct DecisionSummary {
+  pub text: String,
+  pub file_order: u32,
+  pub status: DecisionStatus,
+  pub num_options: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct WorkspaceFileSummary {
+  pub file_path: String, //< Absolute path, same as what get_nodes expects
+  pub relative_path: String, //< Relative to the workspace root - for displaying in the explorer
+  pub title: String,
+  pub decisions: Vec<DecisionSummary>,
+  pub diagnostics: Vec<Diagnostic>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct FileDiagnostic {
+  pub file_path: String,
+  pub diagnostic: Diagnostic,
+}
+
+impl WorkspaceFile {
+  /// Parse errors or validation results for this file
+  pub fn diagnostics(&self) -> Vec<Diagnostic> {
+    match &self.nodes {
+      Ok(nodes) => validate_with(nodes, &self.config),
+      Err(err) => vec![Diagnostic::error(None, err.clone())],
+    }
+  }
+
+  pub fn decisions(&self) -> Vec<DecisionSummary> {
+    let Ok(nodes) = &self.nodes else { return Vec::new(); };
+    return nodes.nodes.iter()
+      .filter(|node| node.type_is == Some(NodeType::Decision))
+      .map(|decision| DecisionSummary{
+        text: decision.text.clone(),
+        file_order: decision.file_order,
+        status: DecisionStatus::from_text(&decision.text),
+        num_options: nodes.nodes.iter()
+          .filter(|node| node.type_is == Some(NodeType::Option) && node.parent_idxs.contains(&decision.file_order))
+          .count() as u32,
+      })
+      .collect();
+  }
+}
+
+impl Workspace {
+  /// Scan the root for decision files & parse them all - in parallel with the `parallel` feature
+  pub fn open(root: PathBuf) -> Result<Workspace, Box<dyn Error>> {
+    if !root.is_dir() { Err(format!("Workspace root '{}' is not a directory", root.display()))? }
+    let mut paths = Vec::new();
+    find_decision_files(&root, &mut paths)?;
+    paths.sort();
+    #[cfg(feature = "parallel")]
+    let paths = paths.into_par_iter();
+    #[cfg(not(feature = "parallel"))]
+    let paths = paths.into_iter();
+    let files = paths
+      .map(load_file)
+      .collect();
+    return Ok(Workspace{root, files});
+  }
+
+  pub fn file(&self, path: &Path) -> Option<&WorkspaceFile> {
+    return self.files.iter().find(|file| file.path == path);
+  }
+
+  pub fn summaries(&self) -> Vec<WorkspaceFileSummary> {
+    return self.files.iter().map(|file| {
+      let title = match &file.nodes { Ok(nodes) => nodes.title.clone(), Err(_) => String::new() };
+      WorkspaceFileSummary{
+        file_path: file.path.to_string_lossy().to_string(),
+        relative_path: file.path.strip_prefix(&self.root).unwrap_or(&file.path).to_string_lossy().to_string(),
+        title,
+        decisions: file.decisions(),
+        diagnostics: file.diagnostics(),
+      }
+    }).collect();
+  }
+
+  /// Diagnostics across every file, paired with the file they came from
+  pub fn diagnostics(&self) -> Vec<FileDiagnostic> {
+    return self.files.iter()
+      .flat_map(|file| {
+        let file_path = file.path.to_string_lossy().to_string();
+        file.diagnostics().into_iter().map(move |diagnostic| FileDiagnostic{file_path: file_path.clone(), diagnostic})
+      })
+      .collect();
+  }
+}
+
+/// Parsed with the file's `.mdt.toml` - kept with the nodes so validation uses its rules too
+fn load_file(path: PathBuf) -> WorkspaceFile {
+  let config = match Config::discover(&path) {
+    Ok(config) => config,
+    Err(err) => return WorkspaceFile{path, nodes: Err(err.to_string()), config: Config::default()},
+  };
+  let nodes = read_to_string(&path).map_err(|err| err.to_string())
+    .and_then(|content| parse_str_with(&content, &config).map_err(|err| err.to_string()));
+  return WorkspaceFile{path, nodes, config};
+}
+
+/// Only the first line needs read to know if its a decision file
+fn has_required_header(path: &Path) -> bool {
+  let Ok(file) = File::open(path) else { return false; };
+  let mut first_line = String::new();
+  return BufReader::new(file).read_line(&mut first_line).is_ok() && first_line.contains(REQUIRED_HEADER);
+}
+
+/// Recursively collect `*.md` files with the required header - skipping hidden entries & not following symlinks
+fn find_decision_files(dir: &Path, found: &mut Vec<PathBuf>) -> Result<(), Box<dyn Error>> {
+  for entry in read_dir(dir)? {
+    let entry = entry?;
+    if entry.file_name().to_string_lossy().starts_with('.') { continue; }
+    let path = entry.path();
+    let file_type = entry.file_type()?;
+    if file_type.is_dir() {
+      find_decision_files(&path, found)?;
+    } else if file_type.is_file() && path.extension().is_some_and(|ext| ext == "md") && has_required_header(&path) {
+      found.push(path);
+    }
+  }
+  return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::file_parse::DATA_DIR;
+
+  #[test]
+  fn test_open_workspace() {
+    let workspace = Workspace::open(DATA_DIR.clone()).unwrap();
+    // 00_invalid_file.md is missing the header so shouldn't be picked up
+    assert!(workspace.file(&DATA_DIR.join("00_invalid_file.md")).is_none());
+    let encoding = workspace.file(&DATA_DIR.join("03_basic_encoding.md")).expect("03_basic_encoding.md not found");
+    let decisions = encoding.decisions();
+    assert_eq!(decisions.len(), 2);
+    assert_eq!(decisions[0].text, "What to do about X?");
+    assert_eq!(decisions[0].num_options, 2);
+    assert_eq!(decisions[0].status, DecisionStatus::Open);
+    assert!(workspace.diagnostics().is_empty());
+
+    let summaries = workspace.summaries();
+    assert_eq!(summaries.len(), workspace.files.len());
+    assert!(summaries.iter().any(|summary| summary.relative_path == "03_basic_encoding.md"));
+  }
+
+  #[test]
+  fn test_open_workspace_not_a_dir() {
+    assert!(Workspace::open(DATA_DIR.join("01_bullets.md")).is_err());
+  }
+}
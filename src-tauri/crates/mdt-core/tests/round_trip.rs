@@ -0,0 +1,64 @@
+// Property tests - any nodes the app can hold must come back the same after a save & reload
+
+use mdt_core::file_parse::parse_str;
+use mdt_core::file_write::nodes_to_string;
+use mdt_core::structs::{Node, NodeType, Nodes};
+use proptest::prelude::*;
+use std::fs::{read_dir, read_to_string};
+use std::path::{Path, PathBuf};
+
+const MAX_NODES: usize = 12;
+
+fn node_type() -> impl Strategy<Value = NodeType> {
+  return prop_oneof![
+    Just(NodeType::Decision), Just(NodeType::Option), Just(NodeType::Pro), Just(NodeType::Con), Just(NodeType::Note),
+    Just(NodeType::Assumption), Just(NodeType::Risk), Just(NodeType::Chance), Just(NodeType::Outcome),
+  ];
+}
+
+/// Comparative links a Pro / Con may have instead of its implicit parent - (same type, diff type)
+fn comparative_idxs() -> impl Strategy<Value = Option<(Vec<u32>, Vec<u32>)>> {
+  let idxs = |min| prop::collection::vec(0..MAX_NODES as u32, min..4);
+  return prop::option::of((idxs(1), idxs(0)));
+}
+
+/// Nodes in file order - levels are random, so a node's implicit parent is the nearest line above at a lower level
+fn arb_nodes() -> impl Strategy<Value = Nodes> {
+  let node = (node_type(), "[^\r\n]*", 0..4u32, comparative_idxs());
+  return ("[a-z]{1,8}", prop::collection::vec(node, 0..MAX_NODES)).prop_map(|(name, raw_nodes)| {
+    let mut parents: Vec<(u32, u32)> = Vec::new(); //< (level, file_order) of potential parents
+    let mut nodes = Vec::new();
+    for (file_order, (type_is, text, level, comparative)) in raw_nodes.into_iter().enumerate() {
+      while parents.last().is_some_and(|(parent_level, _)| *parent_level >= level) { parents.pop(); }
+      let implicit_parent: Vec<u32> = parents.last().map(|(_, idx)| *idx).into_iter().collect();
+      parents.push((level, file_order as u32));
+      let (parent_idxs, parent_idxs_diff_type) = match (&type_is, comparative) {
+        (NodeType::Pro | NodeType::Con, Some(links)) => links,
+        _ => (implicit_parent, Vec::new()),
+      };
+      nodes.push(Node::new(text, type_is, file_order as u32, level, parent_idxs, parent_idxs_diff_type));
+    }
+    Nodes{title: format!("# {} (md-decision-trees)", name), nodes}
+  });
+}
+
+proptest! {
+  #[test]
+  fn test_write_then_parse(nodes in arb_nodes()) {
+    let content = nodes_to_string(&nodes, Path::new("round_trip.md")).unwrap();
+    prop_assert_eq!(parse_str(&content).unwrap(), nodes, "content:\n{}", content);
+  }
+}
+
+/// Inputs found by fuzzing / the property test above - each must parse without panicking, and survive a save
+#[test]
+fn test_regressions() {
+  let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("test/regressions");
+  for entry in read_dir(&dir).unwrap() {
+    let path = entry.unwrap().path();
+    let content = read_to_string(&path).unwrap();
+    let Ok(nodes) = parse_str(&content) else { continue; };
+    let written = nodes_to_string(&nodes, &path).unwrap();
+    assert_eq!(parse_str(&written).unwrap(), nodes, "{} changed after a save", path.display());
+  }
+}
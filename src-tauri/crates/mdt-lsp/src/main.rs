@@ -0,0 +1,493 @@
+// Language server for decision files - ex. for editing them in VS Code / Neovim, talks LSP over stdin / stdout
+
+use mdt_core::analysis::Model;
+use mdt_core::config::Config;
+use mdt_core::file_parse::parse_str_with;
+use mdt_core::file_write::{node_to_line_under, nodes_to_string_with, ImplicitParents};
+use mdt_core::graph::Graph;
+use mdt_core::node_meta::{AssumptionStatus, RiskRating};
+use mdt_core::source_map::{node_spans, parse_error_line, renumber_links, NodeSpan};
+use mdt_core::structs::{Node, Nodes, NodeType};
+use mdt_core::validate::{validate_with, Severity};
+
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, DidSaveTextDocument, Notification as _, PublishDiagnostics,
+};
+use lsp_types::request::{CodeActionRequest, Completion, Formatting, GotoDefinition, HoverRequest, Request as _};
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, CodeActionProviderCapability, CompletionItem,
+    CompletionItemKind, CompletionOptions, CompletionParams, Diagnostic, DiagnosticSeverity, DocumentFormattingParams,
+    GotoDefinitionParams, GotoDefinitionResponse, Hover, HoverContents, HoverParams, HoverProviderCapability, Location,
+    MarkupContent, MarkupKind, OneOf, Position, PublishDiagnosticsParams, Range, ServerCapabilities, TextDocumentSyncCapability,
+    TextDocumentSyncKind, TextDocumentSyncOptions, TextDocumentSyncSaveOptions, TextEdit, Url, WorkspaceEdit,
+};
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::error::Error;
+use std::process::ExitCode;
+
+const RENUMBER_TITLE: &str = "Renumber comparative indexes";
+
+/// An open file - saved is what the comparative indexes are renumbered against
+struct Document {
+    content: String,
+    saved: Option<Nodes>,
+    config: Config,
+    config_error: Option<String>, //< Shown as a diagnostic - the built in format is used until it's fixed
+}
+
+impl Document {
+    fn new(uri: &Url, content: String) -> Self {
+        let mut document = Document { content, saved: None, config: Config::default(), config_error: None };
+        document.load_config(uri);
+        document.saved = parse_str_with(&document.content, &document.config).ok();
+        return document;
+    }
+
+    /// The file's `.mdt.toml` - documents that aren't files on disk use the built in format
+    fn load_config(&mut self, uri: &Url) {
+        let Ok(path) = uri.to_file_path() else { return };
+        (self.config, self.config_error) = match Config::discover(&path) {
+            Ok(config) => (config, None),
+            Err(err) => (Config::default(), Some(err.to_string())),
+        };
+    }
+
+    fn nodes(&self) -> Option<(Nodes, Vec<NodeSpan>)> {
+        return parse_str_with(&self.content, &self.config).ok().map(|nodes| (nodes, node_spans(&self.content, &self.config)));
+    }
+
+    /// Nodes with indexes that shifted since the last save pointed back at their original nodes
+    fn renumbered(&self, nodes: &Nodes) -> Option<Nodes> {
+        return renumber_links(self.saved.as_ref()?, nodes);
+    }
+}
+
+#[derive(Default)]
+struct Server {
+    documents: HashMap<Url, Document>,
+}
+
+fn main() -> ExitCode {
+    if let Err(err) = run() {
+        eprintln!("mdt-lsp: {}", err);
+        return ExitCode::FAILURE;
+    }
+    return ExitCode::SUCCESS;
+}
+
+fn run() -> Result<(), Box<dyn Error + Send + Sync>> {
+    let (connection, io_threads) = Connection::stdio();
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Options(TextDocumentSyncOptions {
+            open_close: Some(true),
+            change: Some(TextDocumentSyncKind::FULL),
+            save: Some(TextDocumentSyncSaveOptions::Supported(true)),
+            ..Default::default()
+        })),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        definition_provider: Some(OneOf::Left(true)),
+        completion_provider: Some(CompletionOptions { trigger_characters: Some(vec![",".to_string()]), ..Default::default() }),
+        document_formatting_provider: Some(OneOf::Left(true)),
+        code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+        ..Default::default()
+    };
+    connection.initialize(serde_json::to_value(capabilities)?)?;
+
+    let mut server = Server::default();
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection.handle_shutdown(&request)? {
+                    break;
+                }
+                connection.sender.send(Message::Response(server.handle_request(request)))?;
+            }
+            Message::Notification(notification) => {
+                if let Some(uri) = server.handle_notification(notification)? {
+                    let diagnostics = server.documents.get(&uri).map_or(Vec::new(), diagnostics);
+                    let params = PublishDiagnosticsParams { uri, diagnostics, version: None };
+                    connection.sender.send(Message::Notification(Notification::new(PublishDiagnostics::METHOD.to_string(), params)))?;
+                }
+            }
+            Message::Response(_) => {}
+        }
+    }
+    io_threads.join()?;
+    return Ok(());
+}
+
+fn params<P: DeserializeOwned>(request: Request) -> Result<P, serde_json::Error> {
+    return serde_json::from_value(request.params);
+}
+
+impl Server {
+    fn handle_request(&self, request: Request) -> Response {
+        let id = request.id.clone();
+        let result = match request.method.as_str() {
+            HoverRequest::METHOD => params(request).map(|params| serde_json::to_value(self.hover(params))),
+            GotoDefinition::METHOD => params(request).map(|params| serde_json::to_value(self.definition(params))),
+            Completion::METHOD => params(request).map(|params| serde_json::to_value(self.completion(params))),
+            Formatting::METHOD => params(request).map(|params| serde_json::to_value(self.formatting(params))),
+            CodeActionRequest::METHOD => params(request).map(|params| serde_json::to_value(self.code_actions(params))),
+            method => return Response::new_err(id, ErrorCode::MethodNotFound as i32, format!("Unsupported request {}", method)),
+        };
+        return match result.and_then(|value| value) {
+            Ok(value) => Response::new_ok(id, value),
+            Err(err) => Response::new_err(id, ErrorCode::InvalidParams as i32, err.to_string()),
+        };
+    }
+
+    /// Returns the document to publish diagnostics for, if any
+    fn handle_notification(&mut self, notification: Notification) -> Result<Option<Url>, serde_json::Error> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params: lsp_types::DidOpenTextDocumentParams = serde_json::from_value(notification.params)?;
+                let document = Document::new(&params.text_document.uri, params.text_document.text);
+                self.documents.insert(params.text_document.uri.clone(), document);
+                return Ok(Some(params.text_document.uri));
+            }
+            DidChangeTextDocument::METHOD => {
+                let params: lsp_types::DidChangeTextDocumentParams = serde_json::from_value(notification.params)?;
+                let (Some(document), Some(change)) = (self.documents.get_mut(&params.text_document.uri), params.content_changes.last()) else {
+                    return Ok(None);
+                };
+                document.content = change.text.clone(); //< Full sync - the last change is the whole document
+                return Ok(Some(params.text_document.uri));
+            }
+            DidSaveTextDocument::METHOD => {
+                let params: lsp_types::DidSaveTextDocumentParams = serde_json::from_value(notification.params)?;
+                let Some(document) = self.documents.get_mut(&params.text_document.uri) else { return Ok(None) };
+                document.load_config(&params.text_document.uri); //< Picks up .mdt.toml edits
+                if let Ok(nodes) = parse_str_with(&document.content, &document.config) {
+                    document.saved = Some(nodes);
+                }
+                return Ok(Some(params.text_document.uri));
+            }
+            DidCloseTextDocument::METHOD => {
+                let params: lsp_types::DidCloseTextDocumentParams = serde_json::from_value(notification.params)?;
+                self.documents.remove(&params.text_document.uri);
+                return Ok(Some(params.text_document.uri)); //< Clears its diagnostics
+            }
+            _ => return Ok(None),
+        }
+    }
+
+    /// The node on the hovered line - comparative nodes show the Options they are a Pro / Con for
+    fn hover(&self, params: HoverParams) -> Option<Hover> {
+        let position = params.text_document_position_params;
+        let document = self.documents.get(&position.text_document.uri)?;
+        let (nodes, spans) = document.nodes()?;
+        let file_order = spans.iter().position(|span| span.line == position.position.line)?;
+        let node = &nodes.nodes[file_order];
+        let type_name = node.type_is.as_ref().map_or(String::from("Node"), |type_is| document.config.type_name(type_is));
+        let describe_targets = |idxs: &Vec<u32>| -> String {
+            idxs.iter()
+                .map(|idx| match (nodes.nodes.get(*idx as usize), spans.get(*idx as usize)) {
+                    (Some(target), Some(span)) => format!("* {} '{}' (line {})", idx, target.text, span.line + 1),
+                    _ => format!("* {} - no such node", idx),
+                })
+                .collect::<Vec<String>>()
+                .join("\n")
+        };
+        let mut value = format!("**{}** {}", type_name, file_order);
+        match node.type_is {
+            Some(NodeType::Pro) | Some(NodeType::Con) => {
+                let (same, diff) = if node.type_is == Some(NodeType::Pro) { ("Pro", "Con") } else { ("Con", "Pro") };
+                value.push_str(&format!("\n\n{} for:\n{}", same, describe_targets(&node.parent_idxs)));
+                if !node.parent_idxs_diff_type.is_empty() {
+                    value.push_str(&format!("\n\n{} for:\n{}", diff, describe_targets(&node.parent_idxs_diff_type)));
+                }
+            }
+            Some(NodeType::Option) => {
+                let graph = Graph::new(&nodes);
+                let num_pros = graph.children_of_type(file_order as u32, NodeType::Pro).len();
+                let num_cons = graph.children_of_type(file_order as u32, NodeType::Con).len();
+                value.push_str(&format!("\n\n{} pros, {} cons", num_pros, num_cons));
+            }
+            Some(NodeType::Assumption) => {
+                value.push_str(&format!("\n\n{:?}", AssumptionStatus::from_text(&node.text)));
+            }
+            Some(NodeType::Chance) => {
+                value.push_str(&format!("\n\nExpected value {}", Model::new(&nodes).expected_value(file_order as u32)));
+            }
+            Some(NodeType::Risk) => {
+                let rating = RiskRating::from_text(&node.text);
+                value.push_str(&format!("\n\n{:?} likelihood, {:?} impact", rating.likelihood, rating.impact));
+            }
+            _ => {}
+        }
+        return Some(Hover { contents: HoverContents::Markup(MarkupContent { kind: MarkupKind::Markdown, value }), range: None });
+    }
+
+    /// From a comparative index to the line of the node it references
+    fn definition(&self, params: GotoDefinitionParams) -> Option<GotoDefinitionResponse> {
+        let position = params.text_document_position_params;
+        let document = self.documents.get(&position.text_document.uri)?;
+        let spans = node_spans(&document.content, &document.config);
+        let span = spans.iter().find(|span| span.line == position.position.line)?;
+        let character = position.position.character;
+        let index = span.indexes.iter().find(|index| index.start <= character && character <= index.end)?;
+        let target = spans.get(index.idx as usize)?;
+        let range = Range::new(Position::new(target.line, target.type_start), Position::new(target.line, target.line_end));
+        return Some(GotoDefinitionResponse::Scalar(Location::new(position.text_document.uri, range)));
+    }
+
+    /// Node types at the start of a bullet - Options after a comparative type's comma
+    fn completion(&self, params: CompletionParams) -> Option<Vec<CompletionItem>> {
+        let position = params.text_document_position;
+        let document = self.documents.get(&position.text_document.uri)?;
+        let line = document.content.lines().nth(position.position.line as usize)?;
+        let before: String = String::from_utf16_lossy(&line.encode_utf16().take(position.position.character as usize).collect::<Vec<u16>>());
+        let token = before.trim_start().trim_start_matches("* ");
+        if token.contains(':') || token.contains(' ') {
+            return None;
+        }
+        if token.ends_with(',') {
+            let nodes = parse_str_with(&document.content, &document.config).ok()?;
+            let options = nodes.nodes.iter().filter(|node| node.type_is == Some(NodeType::Option));
+            return Some(options.map(|option| CompletionItem {
+                label: option.file_order.to_string(),
+                kind: Some(CompletionItemKind::REFERENCE),
+                detail: Some(option.text.clone()),
+                ..Default::default()
+            }).collect());
+        }
+        let node_types = document.config.node_types();
+        let allowed = node_types.iter().filter(|node_type| document.config.is_allowed(node_type));
+        return Some(allowed.map(|node_type| {
+            let token = document.config.token(node_type);
+            CompletionItem {
+                label: token.to_string(),
+                kind: Some(CompletionItemKind::KEYWORD),
+                detail: Some(document.config.type_name(node_type)),
+                insert_text: Some(format!("{}: ", token)),
+                ..Default::default()
+            }
+        }).collect());
+    }
+
+    /// Rewrites the whole file the way the app saves it - with shifted comparative indexes renumbered
+    fn formatting(&self, params: DocumentFormattingParams) -> Option<Vec<TextEdit>> {
+        let document = self.documents.get(&params.text_document.uri)?;
+        let (nodes, _) = document.nodes()?;
+        let nodes = document.renumbered(&nodes).unwrap_or(nodes);
+        let mut formatted = nodes_to_string_with(&nodes, std::path::Path::new(""), &document.config).ok()?;
+        if document.content.ends_with('\n') {
+            formatted.push('\n');
+        }
+        if document.content.contains("\r\n") {
+            formatted = formatted.replace('\n', "\r\n");
+        }
+        if formatted == document.content {
+            return Some(Vec::new());
+        }
+        let num_lines = document.content.lines().count() as u32 + 1;
+        return Some(vec![TextEdit::new(Range::new(Position::new(0, 0), Position::new(num_lines, 0)), formatted)]);
+    }
+
+    /// Quick fix to renumber comparative indexes that shifted since the last save
+    fn code_actions(&self, params: CodeActionParams) -> Option<Vec<CodeActionOrCommand>> {
+        let document = self.documents.get(&params.text_document.uri)?;
+        let (nodes, spans) = document.nodes()?;
+        let renumbered = document.renumbered(&nodes)?;
+        let mut edits = Vec::new();
+        for (node, implicit_parent, span) in shifted(&nodes, &renumbered, &spans) {
+            let range = Range::new(Position::new(span.line, span.type_start), Position::new(span.line, span.text_start));
+            edits.push(TextEdit::new(range, type_token(node, implicit_parent, &document.config).ok()?));
+        }
+        let action = CodeAction {
+            title: RENUMBER_TITLE.to_string(),
+            kind: Some(CodeActionKind::QUICKFIX),
+            edit: Some(WorkspaceEdit { changes: Some(HashMap::from([(params.text_document.uri, edits)])), ..Default::default() }),
+            ..Default::default()
+        };
+        return Some(vec![CodeActionOrCommand::CodeAction(action)]);
+    }
+}
+
+/// A node's type & indexes as written before its text - ex. `P,1,4-C,2: `
+fn type_token(node: &Node, implicit_parent: Option<u32>, config: &Config) -> Result<String, Box<dyn Error>> {
+    return node_to_line_under(&Node { level: 0, text: String::new(), ..node.clone() }, implicit_parent, config);
+}
+
+/// Renumbered nodes whose indexes differ from the file's - with the parent their indent gives them & where they are
+fn shifted<'a>(
+    nodes: &'a Nodes, renumbered: &'a Nodes, spans: &'a [NodeSpan]
+) -> impl Iterator<Item = (&'a Node, Option<u32>, &'a NodeSpan)> {
+    let mut implicit_parents = ImplicitParents::default();
+    return renumbered.nodes.iter().zip(nodes.nodes.iter()).zip(spans.iter())
+        .map(move |((new, old), span)| (new, old, implicit_parents.next(new), span))
+        .filter(|(new, old, _, _)| new.parent_idxs != old.parent_idxs || new.parent_idxs_diff_type != old.parent_idxs_diff_type)
+        .map(|(new, _, implicit_parent, span)| (new, implicit_parent, span));
+}
+
+/// Parse errors & validation problems - plus hints for comparative indexes that shifted since the last save
+fn diagnostics(document: &Document) -> Vec<Diagnostic> {
+    let line_range = |span: &NodeSpan| Range::new(Position::new(span.line, span.type_start), Position::new(span.line, span.line_end));
+    let mut diagnostics: Vec<Diagnostic> = document.config_error.iter()
+        .map(|err| Diagnostic::new_simple(Range::default(), err.clone()))
+        .collect();
+    let nodes = match parse_str_with(&document.content, &document.config) {
+        Ok(nodes) => nodes,
+        Err(err) => {
+            let line = parse_error_line(&document.content, &document.config).unwrap_or(0);
+            let range = Range::new(Position::new(line, 0), Position::new(line + 1, 0));
+            diagnostics.push(Diagnostic::new_simple(range, err.to_string()));
+            return diagnostics;
+        }
+    };
+    let spans = node_spans(&document.content, &document.config);
+    diagnostics.extend(validate_with(&nodes, &document.config).into_iter().map(|diagnostic| {
+        let range = diagnostic.file_order.and_then(|file_order| spans.get(file_order as usize)).map_or(Range::default(), line_range);
+        let severity = match diagnostic.severity {
+            Severity::Error => DiagnosticSeverity::ERROR,
+            Severity::Warning => DiagnosticSeverity::WARNING,
+        };
+        Diagnostic::new(range, Some(severity), None, Some("mdt".to_string()), diagnostic.message, None, None)
+    }));
+    if let Some(renumbered) = document.renumbered(&nodes) {
+        for (_, _, span) in shifted(&nodes, &renumbered, &spans) {
+            let range = Range::new(Position::new(span.line, span.type_start), Position::new(span.line, span.text_start));
+            let message = format!("Indexes shifted since the last save - use '{}' or format the file to keep its links", RENUMBER_TITLE);
+            diagnostics.push(Diagnostic::new(range, Some(DiagnosticSeverity::INFORMATION), None, Some("mdt".to_string()), message, None, None));
+        }
+    }
+    return diagnostics;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const CONTENT: &str = "# Test (md-decision-trees)
+
+D: Which database?
+* O: Postgres
+  * P: Mature
+* O: SQLite
+  * P,3-C,1: No server to run";
+
+    /// Like the app's saved file - with a line inserted above the Options since
+    fn inserted(content: &str) -> String {
+        return content.replace("D: Which database?", "D: Which database?\n* N: Decide by Friday");
+    }
+
+    fn uri() -> Url {
+        return Url::parse("untitled:test.md").unwrap(); //< Not a file, so no .mdt.toml is looked for
+    }
+
+    /// A server with one open document - saved as `saved`, currently `content`
+    fn server(saved: &str, content: &str) -> Server {
+        let mut document = Document::new(&uri(), saved.to_string());
+        document.content = content.to_string();
+        return Server { documents: HashMap::from([(uri(), document)]) };
+    }
+
+    /// Params as a client sends them
+    fn at(line: u32, character: u32) -> serde_json::Value {
+        return json!({ "textDocument": { "uri": uri() }, "position": { "line": line, "character": character } });
+    }
+
+    fn document_params<P: DeserializeOwned>(extra: serde_json::Value) -> P {
+        let mut value = json!({ "textDocument": { "uri": uri() } });
+        value.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+        return serde_json::from_value(value).unwrap();
+    }
+
+    /// Applies edits that each stay on one line - like the renumber quick fix's
+    fn apply_edits(content: &str, edits: &[TextEdit]) -> String {
+        let mut lines: Vec<String> = content.lines().map(String::from).collect();
+        for edit in edits {
+            let line = &mut lines[edit.range.start.line as usize];
+            line.replace_range(edit.range.start.character as usize..edit.range.end.character as usize, &edit.new_text);
+        }
+        return lines.join("\n");
+    }
+
+    fn renumber_edits(server: &Server) -> Vec<TextEdit> {
+        let actions = server.code_actions(document_params(json!({
+            "range": { "start": { "line": 0, "character": 0 }, "end": { "line": 0, "character": 0 } },
+            "context": { "diagnostics": [] },
+        }))).unwrap();
+        let CodeActionOrCommand::CodeAction(action) = &actions[0] else { panic!("Expected a code action") };
+        assert_eq!(action.title, RENUMBER_TITLE);
+        return action.edit.as_ref().unwrap().changes.as_ref().unwrap()[&uri()].clone();
+    }
+
+    #[test]
+    fn test_hover() {
+        let server = server(CONTENT, CONTENT);
+        let Some(Hover { contents: HoverContents::Markup(markup), .. }) = server.hover(serde_json::from_value(at(6, 10)).unwrap()) else {
+            panic!("Expected a hover")
+        };
+        assert_eq!(markup.value, "**Pro** 4\n\nPro for:\n* 3 'SQLite' (line 6)\n\nCon for:\n* 1 'Postgres' (line 4)");
+        assert!(server.hover(serde_json::from_value(at(1, 0)).unwrap()).is_none()); //< The blank line after the title
+    }
+
+    #[test]
+    fn test_definition() {
+        let server = server(CONTENT, CONTENT);
+        let Some(GotoDefinitionResponse::Scalar(location)) = server.definition(serde_json::from_value(at(6, 6)).unwrap()) else {
+            panic!("Expected a definition")
+        };
+        assert_eq!(location.range, Range::new(Position::new(5, 2), Position::new(5, 11))); //< `O: SQLite`
+        assert!(server.definition(serde_json::from_value(at(6, 16)).unwrap()).is_none()); //< In the text, not an index
+    }
+
+    #[test]
+    fn test_completion() {
+        let server = server(CONTENT, CONTENT);
+        let options = server.completion(serde_json::from_value(at(6, 6)).unwrap()).unwrap(); //< After `P,`
+        assert_eq!(options.iter().map(|item| item.label.as_str()).collect::<Vec<&str>>(), vec!["1", "3"]);
+        let types = server.completion(serde_json::from_value(at(4, 4)).unwrap()).unwrap(); //< After `* `
+        assert!(types.iter().any(|item| item.label == "P" && item.insert_text.as_deref() == Some("P: ")));
+        assert!(server.completion(serde_json::from_value(at(4, 10)).unwrap()).is_none()); //< In the text
+    }
+
+    #[test]
+    fn test_formatting() {
+        let options = json!({ "options": { "tabSize": 2, "insertSpaces": true } });
+        assert_eq!(server(CONTENT, CONTENT).formatting(document_params(options.clone())), Some(Vec::new()));
+        let edits = server(CONTENT, &inserted(CONTENT)).formatting(document_params(options)).unwrap();
+        assert_eq!(edits.len(), 1);
+        assert_eq!(edits[0].new_text, inserted(CONTENT).replace("P,3-C,1", "P,4-C,2"));
+    }
+
+    #[test]
+    fn test_renumber_multi_index() {
+        let server = server(CONTENT, &inserted(CONTENT));
+        let edits = renumber_edits(&server);
+        assert_eq!(edits, vec![TextEdit::new(Range::new(Position::new(7, 4), Position::new(7, 13)), "P,4-C,2: ".to_string())]);
+        let renumbered = parse_str_with(&apply_edits(&inserted(CONTENT), &edits), &Config::default()).unwrap();
+        assert_eq!(renumbered.nodes[5].parent_idxs, vec![4]);
+        assert_eq!(renumbered.nodes[5].parent_idxs_diff_type, vec![2]);
+        assert!(server.documents[&uri()].renumbered(&renumbered).is_none()); //< Nothing left to renumber
+    }
+
+    #[test]
+    fn test_renumber_single_index() {
+        let content = CONTENT.replace("P,3-C,1: No server to run", "P,1: Same SQL dialect");
+        let server = server(&content, &inserted(&content));
+        let edits = renumber_edits(&server);
+        assert_eq!(edits, vec![TextEdit::new(Range::new(Position::new(7, 4), Position::new(7, 9)), "P,2: ".to_string())]);
+        let renumbered = parse_str_with(&apply_edits(&inserted(&content), &edits), &Config::default()).unwrap();
+        assert_eq!(renumbered.nodes[5].parent_idxs, vec![2]); //< Still Postgres - not the SQLite it's indented under
+        assert!(server.documents[&uri()].renumbered(&renumbered).is_none());
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        assert!(diagnostics(&server(CONTENT, CONTENT).documents[&uri()]).is_empty());
+        let shifted = diagnostics(&server(CONTENT, &inserted(CONTENT)).documents[&uri()]);
+        // The shifted indexes now point at non-Options - plus a hint to renumber them
+        assert_eq!(shifted.iter().map(|diagnostic| diagnostic.severity.unwrap()).collect::<Vec<DiagnosticSeverity>>(),
+            vec![DiagnosticSeverity::WARNING, DiagnosticSeverity::WARNING, DiagnosticSeverity::INFORMATION]);
+        assert!(shifted.iter().all(|diagnostic| diagnostic.range.start.line == 7));
+        let parse_error = diagnostics(&server(CONTENT, &format!("{}\n* Missing a type", CONTENT)).documents[&uri()]);
+        assert_eq!(parse_error.len(), 1);
+        assert_eq!(parse_error[0].range.start.line, 7);
+    }
+}
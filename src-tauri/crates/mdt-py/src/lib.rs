@@ -0,0 +1,258 @@
+//! Python bindings to `mdt-core` - `import mdt` to parse, check, query & write decision files from scripts / notebooks
+//!
+//! Build with `maturin develop` - the classes mirror the app's `Nodes` / `Node` / `NodeType`, and editing them in
+//! place then calling `write_nodes_to_file` saves exactly like the app does.
+
+use mdt_core::file_parse;
+use mdt_core::file_write;
+use mdt_core::query::Query;
+use mdt_core::structs;
+use mdt_core::validate::{self, Severity};
+use mdt_core::workspace::Workspace;
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+use std::collections::HashMap;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+/// Parse errors are `ValueError`s - everything else (missing files, permissions) is an `IOError`
+fn to_py_err(err: Box<dyn Error>) -> PyErr {
+  if err.is::<std::io::Error>() { return PyIOError::new_err(err.to_string()); }
+  return PyValueError::new_err(err.to_string());
+}
+
+#[pyclass(eq, eq_int, module = "mdt")]
+#[derive(Clone, Copy, PartialEq)]
+pub enum NodeType {
+  Decision, Option,
+  Pro, Con,
+  Note,
+  Assumption, Risk,
+  Chance, Outcome,
+  Custom, //< Declared in the project's `.mdt.toml` - its id is the node's `custom_type`
+}
+
+impl From<structs::NodeType> for NodeType {
+  fn from(node_type: structs::NodeType) -> Self {
+    match node_type {
+      structs::NodeType::Decision => NodeType::Decision, structs::NodeType::Option => NodeType::Option,
+      structs::NodeType::Pro => NodeType::Pro, structs::NodeType::Con => NodeType::Con,
+      structs::NodeType::Note => NodeType::Note,
+      structs::NodeType::Assumption => NodeType::Assumption, structs::NodeType::Risk => NodeType::Risk,
+      structs::NodeType::Chance => NodeType::Chance, structs::NodeType::Outcome => NodeType::Outcome,
+      structs::NodeType::Custom(_) => NodeType::Custom,
+    }
+  }
+}
+
+/// Custom types need their id - ex. `(NodeType.Custom, "risk")`
+fn to_core_type(node_type: NodeType, custom_type: &Option<String>) -> structs::NodeType {
+  match node_type {
+    NodeType::Decision => structs::NodeType::Decision, NodeType::Option => structs::NodeType::Option,
+    NodeType::Pro => structs::NodeType::Pro, NodeType::Con => structs::NodeType::Con,
+    NodeType::Note => structs::NodeType::Note,
+    NodeType::Assumption => structs::NodeType::Assumption, NodeType::Risk => structs::NodeType::Risk,
+    NodeType::Chance => structs::NodeType::Chance, NodeType::Outcome => structs::NodeType::Outcome,
+    NodeType::Custom => structs::NodeType::Custom(custom_type.clone().unwrap_or_default()),
+  }
+}
+
+#[pyclass(get_all, set_all, module = "mdt")]
+#[derive(Clone)]
+pub struct Node {
+  pub text: String,
+  pub type_is: Option<NodeType>,
+  pub file_order: u32,
+  pub level: u32,
+  pub parent_idxs: Vec<u32>,
+  pub parent_idxs_diff_type: Vec<u32>,
+  pub custom_type: Option<String>, //< Id of the `.mdt.toml` type when type_is is `NodeType.Custom`
+}
+
+#[pymethods]
+impl Node {
+  #[new]
+  #[pyo3(signature = (text, type_is, file_order, level, parent_idxs=Vec::new(), parent_idxs_diff_type=Vec::new(), custom_type=None))]
+  fn new(text: String, type_is: Option<NodeType>, file_order: u32, level: u32, parent_idxs: Vec<u32>,
+    parent_idxs_diff_type: Vec<u32>, custom_type: Option<String>) -> Self {
+    Node{text, type_is, file_order, level, parent_idxs, parent_idxs_diff_type, custom_type}
+  }
+
+  /// The line the node is saved as - ex. `  * P,3: Mature`
+  fn line(&self) -> PyResult<String> {
+    return file_write::node_to_line(&self.into()).map_err(to_py_err);
+  }
+
+  fn __repr__(&self) -> PyResult<String> {
+    return Ok(format!("Node({}, {:?})", self.file_order, self.line()?.trim_start()));
+  }
+}
+
+impl From<structs::Node> for Node {
+  fn from(node: structs::Node) -> Self {
+    let custom_type = match &node.type_is { Some(structs::NodeType::Custom(id)) => Some(id.clone()), _ => None };
+    Node{text: node.text, type_is: node.type_is.map(NodeType::from), file_order: node.file_order, level: node.level,
+      parent_idxs: node.parent_idxs, parent_idxs_diff_type: node.parent_idxs_diff_type, custom_type}
+  }
+}
+
+impl From<&Node> for structs::Node {
+  fn from(node: &Node) -> Self {
+    structs::Node{text: node.text.clone(), type_is: node.type_is.map(|type_is| to_core_type(type_is, &node.custom_type)), file_order: node.file_order,
+      level: node.level, parent_idxs: node.parent_idxs.clone(), parent_idxs_diff_type: node.parent_idxs_diff_type.clone()}
+  }
+}
+
+/// `nodes` holds references - so `nodes.nodes[0].text = "..."` edits the file's node, like it reads
+#[pyclass(get_all, set_all, module = "mdt")]
+pub struct Nodes {
+  pub title: String,
+  pub nodes: Vec<Py<Node>>,
+}
+
+#[pymethods]
+impl Nodes {
+  #[new]
+  #[pyo3(signature = (title=String::new(), nodes=Vec::new()))]
+  fn new(title: String, nodes: Vec<Py<Node>>) -> Self {
+    Nodes{title, nodes}
+  }
+
+  fn __len__(&self) -> usize {
+    return self.nodes.len();
+  }
+
+  fn __repr__(&self) -> String {
+    return format!("Nodes({:?}, {} nodes)", self.title, self.nodes.len());
+  }
+}
+
+impl Nodes {
+  fn from_core(py: Python<'_>, nodes: structs::Nodes) -> PyResult<Self> {
+    let py_nodes = nodes.nodes.into_iter().map(|node| Py::new(py, Node::from(node))).collect::<PyResult<_>>()?;
+    return Ok(Nodes{title: nodes.title, nodes: py_nodes});
+  }
+
+  fn to_core(&self, py: Python<'_>) -> structs::Nodes {
+    let nodes = self.nodes.iter().map(|node| structs::Node::from(&*node.borrow(py))).collect();
+    return structs::Nodes{title: self.title.clone(), nodes};
+  }
+}
+
+/// Problem found by `validate` - file_order is None when it isn't tied to a single node
+#[pyclass(get_all, module = "mdt")]
+pub struct Diagnostic {
+  pub severity: String, //< "error" or "warning"
+  pub file_order: Option<u32>,
+  pub message: String,
+}
+
+#[pymethods]
+impl Diagnostic {
+  fn __repr__(&self) -> String {
+    return format!("Diagnostic({}, {:?}, {:?})", self.severity, self.file_order, self.message);
+  }
+}
+
+impl From<validate::Diagnostic> for Diagnostic {
+  fn from(diagnostic: validate::Diagnostic) -> Self {
+    let severity = match diagnostic.severity { Severity::Error => "error", Severity::Warning => "warning" };
+    Diagnostic{severity: severity.to_string(), file_order: diagnostic.file_order, message: diagnostic.message}
+  }
+}
+
+#[pyfunction]
+fn parse_file(py: Python<'_>, file_path: PathBuf) -> PyResult<Nodes> {
+  return Nodes::from_core(py, file_parse::parse_file(file_path).map_err(to_py_err)?);
+}
+
+#[pyfunction]
+fn parse_str(py: Python<'_>, content: &str) -> PyResult<Nodes> {
+  return Nodes::from_core(py, file_parse::parse_str(content).map_err(to_py_err)?);
+}
+
+/// Every decision file under a directory - `(nodes per path, parse error per path)` so one bad file doesn't stop a batch
+#[pyfunction]
+fn parse_dir(py: Python<'_>, directory: PathBuf) -> PyResult<(HashMap<String, Nodes>, HashMap<String, String>)> {
+  let workspace = py.allow_threads(|| Workspace::open(directory).map_err(|err| err.to_string()))
+    .map_err(PyIOError::new_err)?;
+  let mut parsed = HashMap::new();
+  let mut errors = HashMap::new();
+  for file in workspace.files {
+    let path = file.path.to_string_lossy().to_string();
+    match file.nodes {
+      Ok(nodes) => { parsed.insert(path, Nodes::from_core(py, nodes)?); },
+      Err(err) => { errors.insert(path, err); },
+    }
+  }
+  return Ok((parsed, errors));
+}
+
+#[pyfunction]
+#[pyo3(signature = (nodes, file_path, num_backups=0))]
+fn write_nodes_to_file(py: Python<'_>, nodes: &Nodes, file_path: PathBuf, num_backups: u32) -> PyResult<()> {
+  let options = file_write::SaveOptions{num_backups};
+  return file_write::write_nodes_to_file_with(nodes.to_core(py), file_path, &options).map_err(to_py_err);
+}
+
+/// The content `write_nodes_to_file` would write - `file_name` is only used for a title if the nodes have none
+#[pyfunction]
+#[pyo3(signature = (nodes, file_name="decision.md"))]
+fn nodes_to_string(py: Python<'_>, nodes: &Nodes, file_name: &str) -> PyResult<String> {
+  return file_write::nodes_to_string(&nodes.to_core(py), Path::new(file_name)).map_err(to_py_err);
+}
+
+#[pyfunction(name = "validate")]
+fn validate_nodes(py: Python<'_>, nodes: &Nodes) -> Vec<Diagnostic> {
+  return validate::validate(&nodes.to_core(py)).into_iter().map(Diagnostic::from).collect();
+}
+
+/// `file_order` of each node matching a query - ex. `options where pros > cons`
+#[pyfunction]
+fn query(py: Python<'_>, query: &str, nodes: &Nodes) -> PyResult<Vec<u32>> {
+  let query = Query::parse(query).map_err(PyValueError::new_err)?;
+  return Ok(query.run(&nodes.to_core(py)));
+}
+
+#[pymodule]
+fn mdt(module: &Bound<'_, PyModule>) -> PyResult<()> {
+  module.add_class::<NodeType>()?;
+  module.add_class::<Node>()?;
+  module.add_class::<Nodes>()?;
+  module.add_class::<Diagnostic>()?;
+  module.add_function(wrap_pyfunction!(parse_file, module)?)?;
+  module.add_function(wrap_pyfunction!(parse_str, module)?)?;
+  module.add_function(wrap_pyfunction!(parse_dir, module)?)?;
+  module.add_function(wrap_pyfunction!(write_nodes_to_file, module)?)?;
+  module.add_function(wrap_pyfunction!(nodes_to_string, module)?)?;
+  module.add_function(wrap_pyfunction!(validate_nodes, module)?)?;
+  module.add_function(wrap_pyfunction!(query, module)?)?;
+  return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_node_line() {
+    // A comparative Pro with no parent of its own type - raises instead of panicking across the FFI
+    let node = Node::new("Cheap".to_string(), Some(NodeType::Pro), 2, 2, vec![], vec![1], None);
+    assert!(node.line().is_err());
+    let node = Node::new("Cheap".to_string(), Some(NodeType::Pro), 2, 2, vec![3], vec![1], None);
+    assert_eq!(node.line().unwrap(), "  * P,3-C,1: Cheap");
+  }
+
+  #[test]
+  fn test_nodes_to_string() {
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| {
+      let nodes = parse_str(py, "# Test (md-decision-trees)\n\nD: X\n* O: Y\n  * P: Z").unwrap();
+      assert!(nodes_to_string(py, &nodes, "x.md").is_ok());
+      nodes.nodes[2].borrow_mut(py).parent_idxs_diff_type = vec![1];
+      nodes.nodes[2].borrow_mut(py).parent_idxs = vec![];
+      let err = nodes_to_string(py, &nodes, "x.md").unwrap_err();
+      assert!(err.is_instance_of::<PyValueError>(py));
+    });
+  }
+}
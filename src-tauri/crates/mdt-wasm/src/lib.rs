@@ -0,0 +1,58 @@
+//! WebAssembly bindings to `mdt-core` - parse, check, query & write decision files in the browser without the app
+//!
+//! Build with `wasm-pack build --target web` - nodes cross to JS as plain objects shaped like the app's `Nodes`
+//! binding, so the same frontend code can use either.
+
+use mdt_core::diff::diff as diff_nodes;
+use mdt_core::file_parse::parse_str;
+use mdt_core::file_write::nodes_to_string;
+use mdt_core::query::Query;
+use mdt_core::structs::Nodes;
+use mdt_core::validate::validate as validate_nodes;
+use serde::Serialize;
+use std::fmt::Display;
+use std::path::Path;
+use wasm_bindgen::prelude::*;
+
+fn to_js_err(err: impl Display) -> JsError {
+  return JsError::new(&err.to_string());
+}
+
+fn from_js(nodes: JsValue) -> Result<Nodes, JsError> {
+  return serde_wasm_bindgen::from_value(nodes).map_err(to_js_err);
+}
+
+fn to_js(value: &impl Serialize) -> Result<JsValue, JsError> {
+  return serde_wasm_bindgen::to_value(value).map_err(to_js_err);
+}
+
+/// A decision file's content as `Nodes` - throws on files the app can't open
+#[wasm_bindgen]
+pub fn parse(content: &str) -> Result<JsValue, JsError> {
+  return to_js(&parse_str(content).map_err(to_js_err)?);
+}
+
+/// The content the app would save for `Nodes` - `file_name` is only used for a title if the nodes have none
+#[wasm_bindgen]
+pub fn write(nodes: JsValue, file_name: &str) -> Result<String, JsError> {
+  return nodes_to_string(&from_js(nodes)?, Path::new(file_name)).map_err(to_js_err);
+}
+
+/// Problems in `Nodes` - ex. comparative indexes pointing at nothing
+#[wasm_bindgen]
+pub fn validate(nodes: JsValue) -> Result<JsValue, JsError> {
+  return to_js(&validate_nodes(&from_js(nodes)?));
+}
+
+/// `file_order` of each node matching a query - ex. `options where pros > cons`
+#[wasm_bindgen]
+pub fn query(query: &str, nodes: JsValue) -> Result<Vec<u32>, JsError> {
+  let query = Query::parse(query).map_err(to_js_err)?;
+  return Ok(query.run(&from_js(nodes)?));
+}
+
+/// Node level changes from `old` to `new`
+#[wasm_bindgen]
+pub fn diff(old: JsValue, new: JsValue) -> Result<JsValue, JsError> {
+  return to_js(&diff_nodes(&from_js(old)?, &from_js(new)?));
+}
@@ -0,0 +1,28 @@
+//! Run with `wasm-pack test --node` - the bindings only work on wasm
+#![cfg(target_arch = "wasm32")]
+
+use mdt_core::structs::{Node, Nodes, NodeType};
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+fn to_js(nodes: &Nodes) -> JsValue {
+  return serde_wasm_bindgen::to_value(nodes).unwrap();
+}
+
+#[wasm_bindgen_test]
+fn test_round_trip() {
+  let content = "# Test (md-decision-trees)\n\nD: X\n* O: Y\n  * P: Z";
+  assert_eq!(mdt_wasm::write(mdt_wasm::parse(content).unwrap(), "x.md").unwrap(), content);
+}
+
+#[wasm_bindgen_test]
+fn test_unwritable_nodes_throw() {
+  // A comparative Pro with no parent of its own type - throws instead of trapping the whole instance
+  let nodes = Nodes{title: String::new(), nodes: vec![
+    Node::new("X".to_string(), NodeType::Decision, 0, 0, vec![], vec![]),
+    Node::new("Y".to_string(), NodeType::Option, 1, 1, vec![0], vec![]),
+    Node::new("Z".to_string(), NodeType::Pro, 2, 2, vec![], vec![1]),
+  ]};
+  assert!(mdt_wasm::write(to_js(&nodes), "x.md").is_err());
+  assert!(mdt_wasm::write(JsValue::from_str("not nodes"), "x.md").is_err());
+}
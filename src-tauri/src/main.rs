@@ -2,13 +2,41 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod mdt;
-use mdt::cmds::{get_nodes, send_nodes};
+use mdt::cmds::{
+    pick_file, pick_new_file, get_templates, new_from_template, get_startup_file, ScopeState,
+    get_nodes, send_nodes, get_config, set_save_options, SaveOptionsState, watch_file, unwatch_file, keep_unsaved_nodes,
+    autosave, get_recovery, discard_recovery,
+    apply_op, undo, redo, history, SessionState,
+    open_workspace, get_workspace_files, get_workspace_diagnostics, WorkspaceState,
+    search_file, search_workspace,
+    query_file, query_workspace,
+    get_file_tracking, get_workspace_tracking,
+    get_file_action_items, get_workspace_action_items,
+    get_rich_text, analyze_nodes, get_sensitivity, prioritize_nodes, record_comparison,
+    diff_since_save, get_file_history, get_nodes_at_revision, diff_revisions,
+};
+use mdt_core::journal::Journal;
+use mdt::watcher::{FileChanged, FileWatcherState};
 use specta_typescript::Typescript;
-use tauri_specta::{collect_commands, Builder};
+use tauri::Manager;
+use tauri_specta::{collect_commands, collect_events, Builder};
 
 fn main() {
     let builder = Builder::<tauri::Wry>::new()
-        .commands(collect_commands![get_nodes, send_nodes]);
+        .commands(collect_commands![
+            pick_file, pick_new_file, get_templates, new_from_template, get_startup_file,
+            get_nodes, send_nodes, get_config, set_save_options, watch_file, unwatch_file, keep_unsaved_nodes,
+            autosave, get_recovery, discard_recovery,
+            apply_op, undo, redo, history,
+            open_workspace, get_workspace_files, get_workspace_diagnostics,
+            search_file, search_workspace,
+            query_file, query_workspace,
+            get_file_tracking, get_workspace_tracking,
+            get_file_action_items, get_workspace_action_items,
+            get_rich_text, analyze_nodes, get_sensitivity, prioritize_nodes, record_comparison,
+            diff_since_save, get_file_history, get_nodes_at_revision, diff_revisions
+        ])
+        .events(collect_events![FileChanged]);
 
     #[cfg(debug_assertions)]
     builder
@@ -17,7 +45,17 @@ fn main() {
 
     tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
+        .manage(ScopeState::default())
+        .manage(WorkspaceState::default())
+        .manage(FileWatcherState::default())
+        .manage(SaveOptionsState::default())
+        .manage(SessionState::default())
         .invoke_handler(builder.invoke_handler())
+        .setup(move |app| {
+            builder.mount_events(app);
+            app.manage(Journal::new(app.path().app_data_dir()?.join("journal")));
+            Ok(())
+        })
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
@@ -3,31 +3,446 @@
 // - Learn more about Tauri commands at https://tauri.app/v1/guides/features/command
 pub mod cmds {
 
-use super::structs::Nodes;
-use super::file_parse::{parse_file, DATA_DIR};
-use super::file_write::write_nodes_to_file;
+use super::watcher::FileWatcherState;
+use mdt_core::structs::Nodes;
+use mdt_core::config::Config;
+use mdt_core::file_parse::{parse_file, parse_str_with, content_hash};
+#[cfg(debug_assertions)]
+use mdt_core::file_parse::DATA_DIR;
+use mdt_core::file_write::{nodes_to_file_content, write_file_atomic, SaveOptions};
+use mdt_core::workspace::{Workspace, WorkspaceFileSummary, FileDiagnostic};
+use mdt_core::search::{self as mdt_search, SearchQuery, SearchHit};
+use mdt_core::query::{Query, QueryMatch};
+use mdt_core::tracking::{self, TrackingReport};
+use mdt_core::action_items::{self, ActionItem};
+use mdt_core::analysis::{self, DecisionAnalysis};
+use mdt_core::rich_text::{self, Span};
+use mdt_core::sensitivity::{self, SensitivityReport};
+use mdt_core::ahp::{self, DecisionPriorities};
+use mdt_core::templates::{self, Template};
+use mdt_core::journal::{Journal, Recovery};
+use mdt_core::history::{History, HistorySummary, Op};
+use mdt_core::diff::{diff, NodeChange};
+use mdt_core::git_history::{self, FileCommit};
+use mdt_core::scope::PathScope;
 
+use std::collections::{BTreeMap, HashMap};
+use std::fs::read_to_string;
 use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, State};
+use tauri_plugin_dialog::{DialogExt, FilePath};
 
-/// Gets path from string after checking if the frontend wants a TEST_FILE instead
-fn get_path_from_string(file_path: String) -> PathBuf {
-    let test_file_prefix_size = 10; 
-    match file_path.get(..test_file_prefix_size) {
-        Some("TEST_FILE:") => return DATA_DIR.join(String::from(&file_path[test_file_prefix_size..])),
-        _ => return PathBuf::from(file_path)
+/// Workspace opened from the file explorer - kept around so it doesn't need rescanned per command
+#[derive(Default)]
+pub struct WorkspaceState(pub Mutex<Option<Workspace>>);
+
+/// Tree model & undo history of a file - kept for as long as the app is open
+pub struct Session {
+    pub nodes: Nodes,
+    pub history: History,
+}
+
+#[derive(Default)]
+pub struct SessionState(pub Mutex<HashMap<PathBuf, Session>>);
+
+impl SessionState {
+    /// Run on a file's session - starting one from the file on disk if needed
+    fn with_session<T>(&self, path: PathBuf, f: impl FnOnce(&mut Session) -> Result<T, String>) -> Result<T, String> {
+        let mut sessions = self.0.lock().map_err(|err| err.to_string())?;
+        if !sessions.contains_key(&path) {
+            let nodes = parse_file(path.clone()).map_err(|err| err.to_string())?;
+            sessions.insert(path.clone(), Session{nodes, history: History::default()});
+        }
+        return f(sessions.get_mut(&path).unwrap());
+    }
+
+    /// Keep the session in sync with what was loaded / saved - history only survives if the nodes didn't change
+    fn sync(&self, path: PathBuf, nodes: &Nodes) -> Result<(), String> {
+        let mut sessions = self.0.lock().map_err(|err| err.to_string())?;
+        if sessions.get(&path).is_some_and(|session| session.nodes == *nodes) { return Ok(()); }
+        sessions.insert(path, Session{nodes: nodes.clone(), history: History::default()});
+        return Ok(());
+    }
+}
+
+/// How send_nodes saves files - set from the frontend's settings. None until then, so each file's `.mdt.toml` is used
+#[derive(Default)]
+pub struct SaveOptionsState(pub Mutex<Option<SaveOptions>>);
+
+/// Files the frontend may use - ones picked in a dialog & the open workspace. Debug builds also allow the test fixtures
+pub struct ScopeState(pub Mutex<PathScope>);
+
+impl Default for ScopeState {
+    fn default() -> Self {
+        let scope = PathScope::default();
+        #[cfg(debug_assertions)]
+        let scope = {
+            let mut scope = scope;
+            let _ = scope.allow_dir(&DATA_DIR); //< Only fails if the fixtures were moved - nothing to allow then
+            scope
+        };
+        return ScopeState(Mutex::new(scope));
+    }
+}
+
+impl ScopeState {
+    /// Path for a file the frontend asked for - refused if it's outside the scope
+    fn resolve(&self, file_path: &str) -> Result<PathBuf, String> {
+        return self.0.lock().map_err(|err| err.to_string())?.resolve(file_path).map_err(|err| err.to_string());
+    }
+
+    /// Allow what the user picked in a dialog - None if they cancelled
+    fn allow_picked(&self, picked: Option<FilePath>) -> Result<Option<String>, String> {
+        let Some(picked) = picked else { return Ok(None); };
+        let path = picked.into_path().map_err(|err| err.to_string())?;
+        let path = self.0.lock().map_err(|err| err.to_string())?.allow_file(&path).map_err(|err| err.to_string())?;
+        return Ok(Some(path.to_string_lossy().to_string()));
+    }
+}
+
+/// Pick a decision file to open - the other commands only accept files picked here or in the open workspace
+#[tauri::command]
+#[specta::specta]
+pub async fn pick_file(app: AppHandle, scope: State<'_, ScopeState>) -> Result<Option<String>, String> {
+    let picked = app.dialog().file().set_title("Open MD Decision File").add_filter("mds", &["md"]).blocking_pick_file();
+    return scope.allow_picked(picked);
+}
+
+/// Pick where to create a new decision file
+#[tauri::command]
+#[specta::specta]
+pub async fn pick_new_file(app: AppHandle, scope: State<'_, ScopeState>) -> Result<Option<String>, String> {
+    let picked = app.dialog().file().set_title("Create New MD Decision File").add_filter("mds", &["md"]).blocking_save_file();
+    return scope.allow_picked(picked);
+}
+
+/// Built in templates & the ones in the user's template directory
+#[tauri::command]
+#[specta::specta]
+pub fn get_templates() -> Result<Vec<Template>, String> {
+    return templates::templates(templates::user_template_dir().as_deref()).map_err(|err| err.to_string());
+}
+
+/// Create a picked new file from a template named in `get_templates` - refuses to replace an existing file
+#[tauri::command]
+#[specta::specta]
+pub fn new_from_template(
+    template: String, values: BTreeMap<String, String>, file_path: String, scope: State<ScopeState>
+) -> Result<Nodes, String> {
+    let path = scope.resolve(&file_path)?;
+    let template = templates::find_template(&template, templates::user_template_dir().as_deref()).map_err(|err| err.to_string())?;
+    return templates::new_from_template(&template, &values, &path).map_err(|err| err.to_string());
+}
+
+/// File to open on startup - a test fixture in debug builds, nothing in release
+#[tauri::command]
+#[specta::specta]
+pub fn get_startup_file() -> Option<String> {
+    #[cfg(debug_assertions)]
+    return Some(DATA_DIR.join("05_comparative_encoding_output.md").to_string_lossy().to_string());
+    #[cfg(not(debug_assertions))]
+    return None;
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_nodes(
+    file_path: String, watcher: State<FileWatcherState>, sessions: State<SessionState>, scope: State<ScopeState>
+) -> Result<Nodes, String> {
+    let path = scope.resolve(&file_path)?;
+    let content = read_to_string(&path).map_err(|err| err.to_string())?;
+    let config = Config::discover(&path).map_err(|err| err.to_string())?;
+    let nodes = parse_str_with(&content, &config).map_err(|err| err.to_string())?;
+    watcher.known_hashes.set(path.clone(), content_hash(&content));
+    sessions.sync(path, &nodes)?;
+    return Ok(nodes);
+}
+
+/// Refuses to save over a file that was changed outside of the app since it was loaded
+#[tauri::command]
+#[specta::specta]
+pub fn send_nodes(
+    nodes: Nodes, file_path: String,
+    watcher: State<FileWatcherState>, save_options: State<SaveOptionsState>, journal: State<Journal>,
+    sessions: State<SessionState>, scope: State<ScopeState>
+) -> Result<(), String> {
+    let path = scope.resolve(&file_path)?;
+    watcher.known_hashes.check_unchanged(&path)?;
+    let options = match save_options.0.lock().map_err(|err| err.to_string())?.clone() {
+        Some(options) => options,
+        None => Config::discover(&path).map_err(|err| err.to_string())?.save,
     };
+    let content = nodes_to_file_content(&nodes, &path).map_err(|err| err.to_string())?;
+    // Known before writing so the watcher doesn't report our own save
+    let prev_hash = watcher.known_hashes.get(&path);
+    watcher.known_hashes.set(path.clone(), content_hash(&content));
+    if let Err(err) = write_file_atomic(&path, &content, &options) {
+        if let Some(prev_hash) = prev_hash { watcher.known_hashes.set(path, prev_hash); }
+        return Err(err.to_string());
+    }
+    sessions.sync(path.clone(), &nodes)?;
+    return Ok(journal.clear(&path).map_err(|err| err.to_string())?);
+}
+
+/// The file's `.mdt.toml` - the built in format if there is none. Parsing & saving already use it, the frontend needs it
+/// for colors & which node types can be created
+#[tauri::command]
+#[specta::specta]
+pub fn get_config(file_path: String, scope: State<ScopeState>) -> Result<Config, String> {
+    return Config::discover(&scope.resolve(&file_path)?).map_err(|err| err.to_string());
+}
+
+/// Change the file's tree model in a way that can be undone - returns the updated nodes
+#[tauri::command]
+#[specta::specta]
+pub fn apply_op(file_path: String, op: Op, sessions: State<SessionState>, scope: State<ScopeState>) -> Result<Nodes, String> {
+    return sessions.with_session(scope.resolve(&file_path)?, |session| {
+        session.history.apply(&mut session.nodes, op)?;
+        Ok(session.nodes.clone())
+    });
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn undo(file_path: String, sessions: State<SessionState>, scope: State<ScopeState>) -> Result<Nodes, String> {
+    return sessions.with_session(scope.resolve(&file_path)?, |session| {
+        session.history.undo(&mut session.nodes)?;
+        Ok(session.nodes.clone())
+    });
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn redo(file_path: String, sessions: State<SessionState>, scope: State<ScopeState>) -> Result<Nodes, String> {
+    return sessions.with_session(scope.resolve(&file_path)?, |session| {
+        session.history.redo(&mut session.nodes)?;
+        Ok(session.nodes.clone())
+    });
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn history(file_path: String, sessions: State<SessionState>, scope: State<ScopeState>) -> Result<HistorySummary, String> {
+    return sessions.with_session(scope.resolve(&file_path)?, |session| Ok(session.history.summary()));
+}
+
+/// Keep a copy of unsaved nodes in case the app closes before they're saved
+#[tauri::command]
+#[specta::specta]
+pub fn autosave(nodes: Nodes, file_path: String, journal: State<Journal>, scope: State<ScopeState>) -> Result<(), String> {
+    return Ok(journal.autosave(&scope.resolve(&file_path)?, nodes).map_err(|err| err.to_string())?);
+}
+
+/// Autosaved nodes newer than the file - check after get_nodes to offer restoring them
+#[tauri::command]
+#[specta::specta]
+pub fn get_recovery(file_path: String, journal: State<Journal>, scope: State<ScopeState>) -> Result<Option<Recovery>, String> {
+    return Ok(journal.recovery(&scope.resolve(&file_path)?).map_err(|err| err.to_string())?);
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn discard_recovery(file_path: String, journal: State<Journal>, scope: State<ScopeState>) -> Result<(), String> {
+    return Ok(journal.clear(&scope.resolve(&file_path)?).map_err(|err| err.to_string())?);
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn set_save_options(options: SaveOptions, save_options: State<SaveOptionsState>) -> Result<(), String> {
+    *save_options.0.lock().map_err(|err| err.to_string())? = Some(options);
+    return Ok(());
+}
+
+/// Emits FileChanged when the file is changed outside of the app - replaces any previously watched file
+#[tauri::command]
+#[specta::specta]
+pub fn watch_file(file_path: String, app: AppHandle, watcher: State<FileWatcherState>, scope: State<ScopeState>) -> Result<(), String> {
+    return Ok(watcher.watch(app, scope.resolve(&file_path)?).map_err(|err| err.to_string())?);
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn unwatch_file(watcher: State<FileWatcherState>) -> Result<(), String> {
+    return watcher.unwatch();
+}
+
+/// Keep the app's nodes over a change reported by FileChanged - the next save replaces the file instead of being refused
+#[tauri::command]
+#[specta::specta]
+pub fn keep_unsaved_nodes(file_path: String, watcher: State<FileWatcherState>, scope: State<ScopeState>) -> Result<(), String> {
+    let path = scope.resolve(&file_path)?;
+    if let Ok(content) = read_to_string(&path) { watcher.known_hashes.set(path, content_hash(&content)); }
+    return Ok(());
+}
+
+/// Pick a directory & scan it for decision files - it becomes the current workspace, so its files can be opened.
+/// None if the user cancelled
+#[tauri::command]
+#[specta::specta]
+pub async fn open_workspace(
+    app: AppHandle, scope: State<'_, ScopeState>, state: State<'_, WorkspaceState>
+) -> Result<Option<Vec<WorkspaceFileSummary>>, String> {
+    let Some(picked) = app.dialog().file().set_title("Open Workspace").blocking_pick_folder() else { return Ok(None); };
+    let root = picked.into_path().map_err(|err| err.to_string())?;
+    let root = scope.0.lock().map_err(|err| err.to_string())?.allow_dir(&root).map_err(|err| err.to_string())?;
+    let workspace = Workspace::open(root).map_err(|err| err.to_string())?;
+    let summaries = workspace.summaries();
+    *state.0.lock().map_err(|err| err.to_string())? = Some(workspace);
+    return Ok(Some(summaries));
+}
+
+/// Files in the current workspace with their decisions & statuses
+#[tauri::command]
+#[specta::specta]
+pub fn get_workspace_files(state: State<WorkspaceState>) -> Result<Vec<WorkspaceFileSummary>, String> {
+    let workspace = state.0.lock().map_err(|err| err.to_string())?;
+    return Ok(workspace.as_ref().ok_or("No workspace is open")?.summaries());
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_workspace_diagnostics(state: State<WorkspaceState>) -> Result<Vec<FileDiagnostic>, String> {
+    let workspace = state.0.lock().map_err(|err| err.to_string())?;
+    return Ok(workspace.as_ref().ok_or("No workspace is open")?.diagnostics());
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn search_file(file_path: String, query: SearchQuery, scope: State<ScopeState>) -> Result<Vec<SearchHit>, String> {
+    let nodes = parse_file(scope.resolve(&file_path)?).map_err(|err| err.to_string())?;
+    return Ok(mdt_search::search_nodes(&file_path, &nodes, &query));
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn search_workspace(query: SearchQuery, state: State<WorkspaceState>) -> Result<Vec<SearchHit>, String> {
+    let workspace = state.0.lock().map_err(|err| err.to_string())?;
+    return Ok(mdt_search::search_workspace(workspace.as_ref().ok_or("No workspace is open")?, &query));
+}
+
+/// Assumptions that aren't validated & high risks in a file
+#[tauri::command]
+#[specta::specta]
+pub fn get_file_tracking(file_path: String, scope: State<ScopeState>) -> Result<TrackingReport, String> {
+    let nodes = parse_file(scope.resolve(&file_path)?).map_err(|err| err.to_string())?;
+    return Ok(tracking::track_nodes(&file_path, &nodes));
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_workspace_tracking(state: State<WorkspaceState>) -> Result<TrackingReport, String> {
+    let workspace = state.0.lock().map_err(|err| err.to_string())?;
+    return Ok(tracking::track_workspace(workspace.as_ref().ok_or("No workspace is open")?));
+}
+
+/// Open `[ ]` items in a file - soonest due first
+#[tauri::command]
+#[specta::specta]
+pub fn get_file_action_items(file_path: String, scope: State<ScopeState>) -> Result<Vec<ActionItem>, String> {
+    let nodes = parse_file(scope.resolve(&file_path)?).map_err(|err| err.to_string())?;
+    return Ok(action_items::open_action_items(&file_path, &nodes));
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_workspace_action_items(state: State<WorkspaceState>) -> Result<Vec<ActionItem>, String> {
+    let workspace = state.0.lock().map_err(|err| err.to_string())?;
+    return Ok(action_items::open_workspace_action_items(workspace.as_ref().ok_or("No workspace is open")?));
+}
+
+/// File orders of the nodes matching a query - see query.rs for the language
+#[tauri::command]
+#[specta::specta]
+pub fn query_file(file_path: String, query: String, scope: State<ScopeState>) -> Result<Vec<u32>, String> {
+    let query = Query::parse(&query)?;
+    let nodes = parse_file(scope.resolve(&file_path)?).map_err(|err| err.to_string())?;
+    return Ok(query.run(&nodes));
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn query_workspace(query: String, state: State<WorkspaceState>) -> Result<Vec<QueryMatch>, String> {
+    let query = Query::parse(&query)?;
+    let workspace = state.0.lock().map_err(|err| err.to_string())?;
+    return Ok(query.run_workspace(workspace.as_ref().ok_or("No workspace is open")?));
+}
+
+/// Inline markdown of each node text - the canvas fetches every node's at once & shows these spans instead of the raw text
+#[tauri::command]
+#[specta::specta]
+pub fn get_rich_text(texts: Vec<String>) -> Vec<Vec<Span>> {
+    return texts.iter().map(|text| rich_text::spans(text)).collect();
+}
+
+/// Expected values & optimal paths of the unsaved nodes - errors if a Chance node's probabilities don't add up to 1
+#[tauri::command]
+#[specta::specta]
+pub fn analyze_nodes(nodes: Nodes) -> Result<Vec<DecisionAnalysis>, String> {
+    return analysis::analyze(&nodes);
+}
+
+/// Where each weight / probability of the unsaved nodes flips the recommended Option - tornado chart data per Decision
+#[tauri::command]
+#[specta::specta]
+pub fn get_sensitivity(nodes: Nodes) -> Result<Vec<SensitivityReport>, String> {
+    return sensitivity::sensitivity(&nodes);
+}
+
+/// Priority vectors, consistency ratios & Option ranking of each Decision's `#ahp` comparisons in the unsaved nodes
+#[tauri::command]
+#[specta::specta]
+pub fn prioritize_nodes(nodes: Nodes) -> Vec<DecisionPriorities> {
+    return ahp::prioritize(&nodes);
+}
+
+/// Record how many times more Option / Pro / Con `a` matters than `b` for a Decision - undoable like any other op
+#[tauri::command]
+#[specta::specta]
+pub fn record_comparison(
+    file_path: String, decision: u32, a: u32, b: u32, ratio: f64, sessions: State<SessionState>, scope: State<ScopeState>
+) -> Result<Nodes, String> {
+    return sessions.with_session(scope.resolve(&file_path)?, |session| {
+        let op = ahp::comparison_op(&session.nodes, decision, a, b, ratio)?;
+        session.history.apply(&mut session.nodes, op)?;
+        Ok(session.nodes.clone())
+    });
+}
+
+/// Changes from the file on disk to the unsaved nodes - for showing what changed since the last save
+#[tauri::command]
+#[specta::specta]
+pub fn diff_since_save(nodes: Nodes, file_path: String, scope: State<ScopeState>) -> Result<Vec<NodeChange>, String> {
+    let saved = parse_file(scope.resolve(&file_path)?).map_err(|err| err.to_string())?;
+    return Ok(diff(&saved, &nodes));
+}
+
+/// Commits that changed the file - newest first
+#[tauri::command]
+#[specta::specta]
+pub fn get_file_history(file_path: String, scope: State<ScopeState>) -> Result<Vec<FileCommit>, String> {
+    return git_history::file_history(&scope.resolve(&file_path)?).map_err(|err| err.to_string());
 }
 
 #[tauri::command]
 #[specta::specta]
-pub fn get_nodes(file_path: String) -> Result<Nodes, String> {
-    return Ok(parse_file(get_path_from_string(file_path)).map_err(|err| err.to_string())?);
+pub fn get_nodes_at_revision(file_path: String, revision: String, scope: State<ScopeState>) -> Result<Nodes, String> {
+    return git_history::nodes_at_revision(&scope.resolve(&file_path)?, &revision).map_err(|err| err.to_string());
 }
 
+/// Changes between two revisions of the file - no new_revision compares against the file on disk
 #[tauri::command]
 #[specta::specta]
-pub fn send_nodes(nodes: Nodes, file_path: String) -> Result<(), String> {
-    return Ok(write_nodes_to_file(nodes, get_path_from_string(file_path)).map_err(|err| err.to_string())?);
+pub fn diff_revisions(
+    file_path: String, old_revision: String, new_revision: Option<String>, scope: State<ScopeState>
+) -> Result<Vec<NodeChange>, String> {
+    let path = scope.resolve(&file_path)?;
+    let old = git_history::nodes_at_revision(&path, &old_revision).map_err(|err| err.to_string())?;
+    let new = match new_revision {
+        Some(new_revision) => git_history::nodes_at_revision(&path, &new_revision),
+        None => parse_file(path),
+    }.map_err(|err| err.to_string())?;
+    return Ok(diff(&old, &new));
 }
 
 }
\ No newline at end of file
@@ -1,8 +1,3 @@
-// Markdown Decision Tree
+// Markdown Decision Tree - the app's side, everything else is in mdt-core
 include!("cmds.rs");
-include!("file_parse.rs");
-include!("file_write.rs");
-include!("structs.rs");
-
-// - Parsers
-include!("parsers/bullet_file_parser.rs");
\ No newline at end of file
+include!("watcher.rs");
@@ -0,0 +1,80 @@
+pub mod watcher {
+
+use mdt_core::file_parse::content_hash;
+use serde::{Serialize, Deserialize};
+use specta::Type;
+use tauri_specta::Event;
+
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::AppHandle;
+
+/// Sent to the frontend when the open file was changed by something other than the app - so it can offer a reload
+#[derive(Serialize, Deserialize, Type, Event, Clone, Debug)]
+pub struct FileChanged {
+  pub file_path: String,
+  pub deleted: bool,
+}
+
+/// Content hash of each file as of the last time the app read or wrote it
+#[derive(Default, Clone)]
+pub struct KnownHashes(Arc<Mutex<HashMap<PathBuf, String>>>);
+
+impl KnownHashes {
+  pub fn get(&self, file_path: &Path) -> Option<String> {
+    return self.0.lock().ok()?.get(file_path).cloned();
+  }
+
+  pub fn set(&self, file_path: PathBuf, hash: String) {
+    if let Ok(mut hashes) = self.0.lock() { hashes.insert(file_path, hash); }
+  }
+
+  /// Refuse to overwrite a file that changed on disk since the app last read or wrote it
+  pub fn check_unchanged(&self, file_path: &Path) -> Result<(), String> {
+    let (Some(known_hash), Ok(disk_content)) = (self.get(file_path), read_to_string(file_path)) else { return Ok(()); };
+    if content_hash(&disk_content) != known_hash {
+      return Err(format!("{} was changed outside of the app since it was loaded - reload it before saving", file_path.display()));
+    }
+    return Ok(());
+  }
+}
+
+/// Watches the currently open file - only one at a time since the app only shows one file
+#[derive(Default)]
+pub struct FileWatcherState {
+  pub known_hashes: KnownHashes,
+  watcher: Mutex<Option<RecommendedWatcher>>,
+}
+
+impl FileWatcherState {
+  /// Replace any previous watch with the given file
+  pub fn watch(&self, app: AppHandle, file_path: PathBuf) -> Result<(), Box<dyn Error>> {
+    let watched_path = file_path.canonicalize()?;
+    // Watch the directory since editors often save by replacing the file, which would end a watch on the file itself
+    let watched_dir = watched_path.parent().ok_or("File has no parent directory")?.to_path_buf();
+    let known_hashes = self.known_hashes.clone();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+      let Ok(event) = res else { return; };
+      if matches!(event.kind, EventKind::Access(_)) || !event.paths.iter().any(|path| *path == watched_path) { return; }
+      let disk_hash = read_to_string(&watched_path).ok().map(|content| content_hash(&content));
+      // Our own saves update the known hash first - so those don't get reported back
+      if disk_hash.is_some() && disk_hash == known_hashes.get(&file_path) { return; }
+      let changed = FileChanged{file_path: file_path.to_string_lossy().to_string(), deleted: disk_hash.is_none()};
+      let _ = changed.emit(&app); //< Only fails once the app is closing - no window left to tell
+    })?;
+    watcher.watch(&watched_dir, RecursiveMode::NonRecursive)?;
+    *self.watcher.lock().map_err(|err| err.to_string())? = Some(watcher);
+    return Ok(());
+  }
+
+  pub fn unwatch(&self) -> Result<(), String> {
+    *self.watcher.lock().map_err(|err| err.to_string())? = None;
+    return Ok(());
+  }
+}
+
+}
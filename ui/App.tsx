@@ -1,6 +1,7 @@
 import "./App.css";
 import {Canvas} from "./canvas/Canvas";
 import {ErrorDisplay} from "./modals/ErrorDisplay";
+import {RecoveryDisplay} from "./modals/RecoveryDisplay";
 import {errorStore} from "./stores/ErrorStore";
 import {canvasStore} from "./stores/CanvasStore";
 import {TopToolbar, LeftToolbar} from "./toolbars/Toolbars";
@@ -16,6 +17,7 @@ function App() {
         <Canvas/>
       </div>
       <ErrorDisplay errorStore={errorStore}/>
+      <RecoveryDisplay canvasStore={canvasStore}/>
     </div>
   );
 }
@@ -1,3 +1,5 @@
+import {NodeType} from "./bindings/bindings"
+
 // Throws if is_true is false - should be an unrecoverable programmer error
 export function assert(is_true: any): void {
   if (!is_true) { throw new Error("Utils.ts - assert - fatal"); }
@@ -6,4 +8,9 @@ export function assert(is_true: any): void {
 export function notNull(element: any): NonNullable<any> {
   if (element == null) { throw new Error("Utils.ts - notNull - fatal"); }
   return element;
-}
\ No newline at end of file
+}
+
+// Lowercase id used for css - ex. "decision", or a .mdt.toml custom type's own id
+export function nodeTypeId(type: NodeType): string {
+  return typeof type === "string" ? type.toLowerCase() : type.Custom;
+}
@@ -3,14 +3,17 @@ import {errorStore} from "../stores/ErrorStore"
 import {canvasStore} from "../stores/CanvasStore"
 import {Node} from "./CanvasElems"
 import {Renderer, RendererComp} from "./Render"
-import {commands, Nodes} from "../bindings/bindings"
+import {commands, events, FileChanged, Nodes} from "../bindings/bindings"
 import {notNull} from "../Utils"
 import {NodeCreator} from "./key-handlers/NodeCreator"
 import {NodeSelector, SelectedNode} from "./key-handlers/NodeSelector"
 import {observer} from 'mobx-react';
 import {reaction} from 'mobx';
+import {ask} from '@tauri-apps/plugin-dialog';
 import React from 'react';
 
+const AUTOSAVE_INTERVAL_MS = 5000;
+
 // What is drawn on / shows nodes
 @observer
 export class Canvas extends React.Component {
@@ -18,6 +21,9 @@ export class Canvas extends React.Component {
   nodeCreator: NodeCreator = new NodeCreator(this, this.renderer);
   nodeSelector: NodeSelector = new NodeSelector(this.renderer);
   lastKey: string = '';
+  askingToReload: boolean = false; //< One change on disk often comes as several events
+  savedNodes: string = ""; //< As last loaded / saved - anything else is unsaved & autosaved
+  autosavedNodes: string = "";
   getSelectedNode() : SelectedNode | null | undefined { return this?.nodeSelector?.current(); }
 
   // Mouse / keyboard Events
@@ -90,13 +96,87 @@ export class Canvas extends React.Component {
     }
     const nodes = result.data;
     if (!nodes) { errorStore.addError(`No nodes in ${filePath}?`); return; }
+    await this.applyConfig(filePath);
     this?.renderer?.renderNodes(nodes);
+    this.savedNodes = this.autosavedNodes = JSON.stringify(this.currentNodes());
+    await this.offerRecovery(filePath);
+  }
+
+  /// Autosaved edits newer than the file - ex. the app closed before they were saved
+  async offerRecovery(filePath: string) {
+    const result = await commands.getRecovery(filePath);
+    if (result.status === "error") {
+      errorStore.addError(`Error checking ${filePath} for autosaved edits - ${result.error}`);
+      return;
+    }
+    canvasStore.setRecovery(result.data);
+  }
+
+  currentNodes(): Nodes {
+    return {title: Node.collectionTitle, nodes: notNull(this.renderer).getNodes()};
+  }
+
+  /// Keep a copy of unsaved edits so they survive a crash - only when they changed since the last autosave
+  async autosave() {
+    const filePath = canvasStore.filePath;
+    if (filePath.length == 0 || canvasStore.recovery) { return; } //< Don't replace an autosave not yet restored
+    const nodes = this.currentNodes();
+    const serialized = JSON.stringify(nodes);
+    if (serialized === this.savedNodes || serialized === this.autosavedNodes) { return; }
+    const result = await commands.autosave(nodes, filePath);
+    if (result.status === "error") { errorStore.addError(`Error autosaving ${filePath} - ${result.error}`); return; }
+    this.autosavedNodes = serialized;
+  }
+
+  /// Colors from the file's .mdt.toml - types without one go back to the defaults in Canvas.css
+  async applyConfig(filePath: string) {
+    const result = await commands.getConfig(filePath);
+    if (result.status === "error") {
+      errorStore.addError(`Error reading the .mdt.toml for ${filePath} - ${result.error}`);
+      return;
+    }
+    const rootStyle = document.documentElement.style;
+    for (const [type, nodeType] of Object.entries(result.data.node_types)) {
+      const properties = [`--node-${type}-color`, ...(type === 'pro' || type === 'con' ? [`--edge-${type}-color`] : [])];
+      for (const property of properties) {
+        if (nodeType.color) { rootStyle.setProperty(property, nodeType.color); } else { rootStyle.removeProperty(property); }
+      }
+    }
+    for (const [id, customType] of Object.entries(result.data.custom_types)) {
+      if (customType.color) { rootStyle.setProperty(`--node-${id}-color`, customType.color); }
+    }
   }
 
   async saveNodesToPath(filePath: string) {
     if (filePath.length == 0) { return; } //< Ensure not change just to clear - allows re-trigger on same name
-    let nodesToSave: Nodes = {title: Node.collectionTitle, nodes: notNull(this.renderer).getNodes()};
-    await commands.sendNodes(nodesToSave, filePath);
+    let nodesToSave: Nodes = this.currentNodes();
+    const result = await commands.sendNodes(nodesToSave, filePath);
+    if (result.status === "error") { errorStore.addError(`Error saving ${filePath} - ${result.error}`); return; }
+    this.savedNodes = this.autosavedNodes = JSON.stringify(nodesToSave); //< Saving also drops the autosave
+  }
+
+  /// Offer to reload the open file after it changed outside the app - or keep the app's nodes to save over it. Only the
+  /// open file is watched, & the event has its resolved path - so it's reported as the path it was opened with
+  async onFileChanged(changed: FileChanged) {
+    const filePath = canvasStore.filePath;
+    if (filePath.length == 0 || this.askingToReload) { return; }
+    if (changed.deleted) {
+      errorStore.addError(`${filePath} was deleted outside of the app - save to write it again`);
+      return;
+    }
+    this.askingToReload = true;
+    try {
+      const reload = await ask(`${filePath} was changed outside of the app. Reload it? Edits made here since it was loaded are lost.`,
+        {title: "File Changed", kind: "warning", okLabel: "Reload", cancelLabel: "Keep My Edits"});
+      if (reload) {
+        await this.loadFile(filePath);
+      } else {
+        const result = await commands.keepUnsavedNodes(filePath);
+        if (result.status === "error") { errorStore.addError(`Error keeping edits to ${filePath} - ${result.error}`); }
+      }
+    } finally {
+      this.askingToReload = false;
+    }
   }
 
   // State Change Updates
@@ -105,14 +185,17 @@ export class Canvas extends React.Component {
     // Bind reactions to store value changes
     reaction(() => canvasStore.filePath, newFilePath => { this.loadFile(newFilePath); })
     reaction(() => canvasStore.saveNodesToFilePath, filePath => { this.saveNodesToPath(filePath); })
+    reaction(() => canvasStore.restoredNodes, nodes => { if (nodes) { this.renderer?.renderNodes(nodes); } })
+    events.fileChanged.listen((event) => { this.onFileChanged(event.payload); });
+    setInterval(() => { this.autosave(); }, AUTOSAVE_INTERVAL_MS);
     // Bind interactions
     document.addEventListener('mouseup', () => { this.clearSelection(); });
     document.addEventListener('keydown', (event) => {this.delegateKeyEvent(event)});
   }
 
-  componentDidMount() {
-    const defaultFile = "TEST_FILE:05_comparative_encoding_output.md";
-    canvasStore.setFilePath(defaultFile);
+  async componentDidMount() {
+    const startupFile = await commands.getStartupFile(); //< A test file in debug builds
+    if (startupFile) { canvasStore.setFilePath(startupFile); }
   }
 
   render() {
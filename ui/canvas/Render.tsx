@@ -1,5 +1,5 @@
 import {Node} from "./CanvasElems"
-import {notNull} from "../Utils"
+import {notNull, nodeTypeId} from "../Utils"
 import {useCallback, useState, useMemo, useRef, useEffect} from 'react';
 import dagre from 'dagre';
 import ReactFlow, { 
@@ -126,6 +126,7 @@ export class Renderer {
   // Render functions
   renderNodes(nodes : fromRust.Nodes): void {
     this._setUpGraph();
+    richTextCache.clear(); //< Only the new file's texts are needed
     this.nodes = [];
     this.edges = [];
     this.nextNodeID = 0; //< Reset to align with parentIDs
@@ -151,13 +152,16 @@ export class Renderer {
   }
 
   renderNode(newNode: fromRust.Node, parentIDs: string[], diffTypeParentIDs: string[]): NodeId {
+    const typeId = newNode.type_is ? nodeTypeId(newNode.type_is) : undefined;
+    const isCustomType = typeof newNode.type_is === "object" && newNode.type_is !== null;
     const newCanvasNode = {
       id: this.nextNodeID.toString(),
       data: { 
         label: newNode.text,
         dataNode: newNode,
       },
-      className: diffTypeParentIDs.length != 0 ? 'node-comparative' : `node-${newNode.type_is?.toLowerCase()}`,
+      className: diffTypeParentIDs.length != 0 ? 'node-comparative' : isCustomType ? 'node-custom' : `node-${typeId}`,
+      style: isCustomType ? {background: `var(--node-${typeId}-color, var(--node-custom-color))`} : undefined,
       position: { x: 0, y: 0 },
       type: 'customNodeComp', // Must align with field name in nodeTypes
     }
@@ -228,7 +232,9 @@ export class Renderer {
     this.doubleClickNode = doubleClickNode; 
   }
   
-  _rerenderNodes(cb?: Callback): void {
+  async _rerenderNodes(cb?: Callback): Promise<void> {
+    // Spans for every text in one call, so nodes show their rich text from the first render
+    await fetchRichText(this.nodes.map(node => withoutTask(node.data.label)));
     if (this.renderGraph) {
       this.renderGraph(this.nodes, this.edges, cb);
     }
@@ -272,6 +278,38 @@ export class Renderer {
   }
 };
 
+// `[ ]` / `[x]` starting a node's text makes it an action item - same rule as node_meta.rs
+const TASK_REGEX = /^\[([ xX])\](?:\s+|$)/;
+
+function withoutTask(text: string): string {
+  const task = text.match(TASK_REGEX);
+  return task ? text.slice(task[0].length) : text;
+}
+
+// Spans of each text shown - filled a render pass at a time, so a node only fetches its own after its text is edited
+const richTextCache = new Map<string, fromRust.Span[]>();
+
+async function fetchRichText(texts: string[]): Promise<void> {
+  const missing = [...new Set(texts)].filter(text => !richTextCache.has(text));
+  if (missing.length == 0) { return; }
+  const found = await fromRust.commands.getRichText(missing);
+  missing.forEach((text, idx) => richTextCache.set(text, found[idx]));
+}
+
+// Inline markdown parsed by the back end (rich_text.rs) - every span is plain text, so nothing here can inject html
+function RichText({ spans }: { spans: fromRust.Span[] }): JSX.Element {
+  return <>{spans.map((span, idx) => {
+    if ("Text" in span) { return <span key={idx}>{span.Text.text}</span>; }
+    if ("Bold" in span) { return <strong key={idx}><RichText spans={span.Bold.children}/></strong>; }
+    if ("Italic" in span) { return <em key={idx}><RichText spans={span.Italic.children}/></em>; }
+    if ("Code" in span) { return <code key={idx} className="node-code">{span.Code.text}</code>; }
+    // TODO - open links in the browser with the opener plugin - following one would navigate the app's own webview
+    return <a key={idx} className="node-link" href={span.Link.url} title={span.Link.url} onClick={(evt) => evt.preventDefault()}>
+      <RichText spans={span.Link.children}/>
+    </a>;
+  })}</>;
+}
+
 function CustomNodeComp({ data, id }: NodeProps): JSX.Element {
   const htmlRef = useRef(null); //< Used to focus / auto-size the text area
   const nodeElementId = `custom-node-${id}`; //< Used to mock click the element to edit
@@ -297,6 +335,22 @@ function CustomNodeComp({ data, id }: NodeProps): JSX.Element {
       data.onTextChange(id, text); // Set in nodesWithHandlers
     }
   }, [id, text, data]);
+  const task = text.match(TASK_REGEX);
+  const taskText = withoutTask(text);
+  const spans = richTextCache.get(taskText); //< Fetched with the render pass, unless the text was edited since
+  const [, setFetchedText] = useState("");
+  useEffect(() => {
+    if (isEditing || richTextCache.has(taskText)) { return; } //< Not while typing - only the finished text is shown
+    let isCurrent = true; //< Ignore a slower reply for text that was already changed again
+    fetchRichText([taskText]).then(() => { if (isCurrent) { setFetchedText(taskText); } });
+    return () => { isCurrent = false; };
+  }, [taskText, isEditing]);
+  const handleTaskToggle = useCallback((evt: React.ChangeEvent<HTMLInputElement>) => {
+    const checkbox = evt.target.checked ? '[x]' : '[ ]';
+    const newText = taskText ? `${checkbox} ${taskText}` : checkbox;
+    setText(newText);
+    data.onTextChange(id, newText);
+  }, [id, taskText, data]);
  
   return (
     <div 
@@ -307,7 +361,11 @@ function CustomNodeComp({ data, id }: NodeProps): JSX.Element {
     >
       <Handle type="target" position={Position.Top} />
       {!isEditing
-      ? <div className="node-content">{text}</div> 
+      ? <div className="node-content">
+          {task && <input type="checkbox" className="node-checkbox" checked={task[1] !== ' '} onChange={handleTaskToggle}
+            onDoubleClick={(evt) => evt.stopPropagation()}/>}
+          {spans ? <RichText spans={spans}/> : taskText}
+        </div>
       : <textarea 
           ref={htmlRef} 
           id={nodeElementTextBoxId} 
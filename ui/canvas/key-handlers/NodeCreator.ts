@@ -1,6 +1,6 @@
 import { Canvas } from "../Canvas";
 import { Renderer, NodeId } from "../Render";
-import { notNull } from "../../Utils"
+import { notNull, nodeTypeId } from "../../Utils"
 import { errorStore } from "../../stores/ErrorStore"
 import * as fromRust from "../../bindings/bindings"
 
@@ -46,7 +46,7 @@ export class NodeCreator {
     let optParentNode = this?.canvas?.getSelectedNode();
     let optParentNodeType = optParentNode?.node.type_is;
     if (!this.canMakeTypeOnParent(optParentNodeType, type)) { 
-      const parentTypeStr = optParentNodeType ? optParentNodeType : "none";
+      const parentTypeStr = optParentNodeType ? nodeTypeId(optParentNodeType) : "none";
       errorStore.addError(`Cannot make node type '${type}' on parent node type '${parentTypeStr}'`);
       return;
     }
@@ -61,16 +61,23 @@ export class NodeCreator {
     } else if (key === 'o') { return "Option";
     } else if (key === 'p') { return "Pro";
     } else if (key === 'c') { return "Con";
-    } else if (key === 'n') { return "Note"; } 
-    errorStore.addError(`Invalid node type shortcut '${key}' - try one of d / o / p /c / n`);
+    } else if (key === 'n') { return "Note";
+    } else if (key === 'a') { return "Assumption";
+    } else if (key === 'r') { return "Risk";
+    } else if (key === 'x') { return "Chance";
+    } else if (key === 'y') { return "Outcome"; }
+    errorStore.addError(`Invalid node type shortcut '${key}' - try one of d / o / p / c / n / a / r / x / y`);
     return null;
   }
 
   canMakeTypeOnParent(parentType: NodeType | null | undefined, newType: NodeType): Boolean {
     if (newType == "Note") { return true; }
+    if (newType == "Assumption" || newType == "Risk") { return parentType == "Decision" || parentType == "Option"; }
     if (!parentType) { return newType == "Decision"; }
     if (parentType == "Decision") { return newType == "Option"; }
-    if (parentType == "Option") { return newType == "Pro" || newType == "Con"; }
+    if (parentType == "Option") { return newType == "Pro" || newType == "Con" || newType == "Chance"; }
+    if (parentType == "Chance") { return newType == "Outcome"; }
+    if (parentType == "Outcome") { return newType == "Chance" || newType == "Decision"; }
     return false;
   }
 };
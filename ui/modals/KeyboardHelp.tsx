@@ -79,6 +79,10 @@ export const KeyboardHelp: React.FC<KeyboardHelpProps> = ({ onClose }) => {
         { keyText: "p", description: "Pro" },
         { keyText: "c", description: "Con" },
         { keyText: "n", description: "Note" },
+        { keyText: "a", description: "Assumption" },
+        { keyText: "r", description: "Risk" },
+        { keyText: "x", description: "Chance" },
+        { keyText: "y", description: "Outcome" },
     ]},
     { title: "Edit:", shortcuts: [
         { keyText: "Ctrl + m, <letter from 'NodeTypes' section>", description: "Make new node" },
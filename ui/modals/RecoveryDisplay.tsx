@@ -0,0 +1,39 @@
+import './resizable_draggable_modal.css';
+import React from 'react';
+import ReactModal from 'react-modal-resizable-draggable';
+import {observer} from 'mobx-react';
+import {CanvasStore} from "../stores/CanvasStore"
+
+interface RecoveryDisplayProps {
+    canvasStore: CanvasStore
+}
+
+const DIFF_PREFIX = {Same: "  ", Added: "+ ", Removed: "- "};
+
+// Offered after opening a file that has autosaved edits newer than it - ex. the app closed before they were saved
+export const RecoveryDisplay: React.FC<RecoveryDisplayProps> = observer(({canvasStore}) => {
+    const recovery = canvasStore.recovery;
+    return (
+        <div>
+            {/* @ts-ignore: ReactModal is missing 'children'?*/}
+            <ReactModal className={"recovery-display-modal"}
+                    initWidth={500} initHeight={350}
+                    isOpen={recovery !== null} onRequestClose={() => {canvasStore.setRecovery(null)}}>
+                <h3 className="recovery-header">Unsaved Edits</h3>
+                <div className="recovery-buttons">
+                    <button onClick={() => {canvasStore.restoreRecovery()}}>Restore</button>
+                    <button onClick={() => {canvasStore.discardRecovery()}}>Discard</button>
+                </div>
+                <p className="recovery-summary">
+                    Edits autosaved {recovery ? new Date(recovery.saved_at * 1000).toLocaleString() : ""} were never saved
+                    to the file - changes from the file:
+                </p>
+                <pre className="recovery-body">
+                    {recovery?.diff.map((line, key) => (
+                        <div key={key} className={`diff-${line.kind.toLowerCase()}`}>{DIFF_PREFIX[line.kind]}{line.text}</div>))
+                    }
+                </pre>
+            </ReactModal>
+        </div>
+    )
+});
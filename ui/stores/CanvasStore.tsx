@@ -1,23 +1,42 @@
 import {makeObservable, observable, action} from 'mobx';
+import {commands, Nodes, Recovery} from "../bindings/bindings"
+import {errorStore} from "./ErrorStore"
 
 export class CanvasStore {
   filePath: string = ""
   saveNodesToFilePath: string = ""
   showKeyboardHelp: boolean = true
+  recovery: Recovery | null = null //< Autosaved edits of the open file, offered to restore
+  restoredNodes: Nodes | null = null
 
   constructor() {
     makeObservable(this, {
       filePath: observable,
       saveNodesToFilePath: observable,
       showKeyboardHelp: observable,
+      recovery: observable.ref,
+      restoredNodes: observable.ref,
       setFilePath: action,
       setSaveNodesToFilePath: action,
       toggleKeyboardHelp: action,
+      setRecovery: action,
+      restoreRecovery: action,
     });
   }
 
   setFilePath = (newFilePath: string) => {
     this.filePath = newFilePath;
+    if (newFilePath.length == 0) { return; } //< Only cleared to re-trigger the same file - keep watching it
+    this.watchFile(newFilePath);
+  }
+
+  /// The open file is watched for changes outside the app - see Canvas.onFileChanged
+  watchFile = async (filePath: string) => {
+    const result = await commands.watchFile(filePath);
+    if (result.status === "error") {
+      errorStore.addError(`Can't watch ${filePath} for changes outside the app - ${result.error}`);
+      await commands.unwatchFile(); //< Don't keep reporting changes to the previous file
+    }
   }
 
   setSaveNodesToFilePath = (newFilePath: string) => {
@@ -27,6 +46,23 @@ export class CanvasStore {
   toggleKeyboardHelp = () => {
     this.showKeyboardHelp = !this.showKeyboardHelp;
   }
+
+  /// Closing without restoring / discarding keeps the autosave - it's offered again next time the file is opened
+  setRecovery = (recovery: Recovery | null) => {
+    this.recovery = recovery;
+  }
+
+  /// Shown as unsaved edits - the autosave is kept until the file is saved
+  restoreRecovery = () => {
+    this.restoredNodes = this.recovery?.nodes ?? null;
+    this.recovery = null;
+  }
+
+  discardRecovery = async () => {
+    const result = await commands.discardRecovery(this.filePath);
+    if (result.status === "error") { errorStore.addError(`Error discarding autosaved edits - ${result.error}`); }
+    this.setRecovery(null);
+  }
 }
 
 export const canvasStore = new CanvasStore();
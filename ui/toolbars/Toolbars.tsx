@@ -2,7 +2,6 @@ import "./Toolbars.css";
 import {canvasStore, CanvasStore} from "../stores/CanvasStore"
 import {errorStore} from "../stores/ErrorStore"
 import {commands, Nodes} from "../bindings/bindings"
-import {open, save} from "@tauri-apps/plugin-dialog"
 import {observer} from 'mobx-react';
 import { ViewMenu } from "./ViewMenu";
 
@@ -26,11 +25,12 @@ interface LeftToolbarProps {
 export const LeftToolbar : React.FC<LeftToolbarProps> = ({canvasStore}) => {
   const openMarkdownFile = async() => {
     try {
-      const filePath = await open({
-        title: "Open MD Decision File", filters: [{name: "mds", extensions: ['md']}], multiple: false});
-      if (filePath) {
+      // Picked by the backend - it only reads / writes files the user picked or in the open workspace
+      const result = await commands.pickFile();
+      if (result.status === "error") { throw result.error; }
+      if (result.data) {
         canvasStore.setFilePath(""); //< clear first to ensure update
-        canvasStore.setFilePath(Array.isArray(filePath) ? filePath[0] : filePath);
+        canvasStore.setFilePath(result.data);
       }
     } catch(err) {
       errorStore.addError(`Error opening file - ${err}`);
@@ -44,7 +44,9 @@ export const LeftToolbar : React.FC<LeftToolbarProps> = ({canvasStore}) => {
 
   const createNewFile = async() => {
     try {
-      const filePath = await save({title: "Create New MD Decision File", filters: [{name: "mds", extensions: ['md']}]})
+      const result = await commands.pickNewFile();
+      if (result.status === "error") { throw result.error; }
+      const filePath = result.data;
       if (filePath) {
         let emptyNodes : Nodes = {title: "", nodes: []}; //< Empty name will make Rust use the file stem
         await commands.sendNodes(emptyNodes, filePath);